@@ -0,0 +1,54 @@
+//! Compare a hot CAS loop on one shared atomic with and without backoff.
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use STM::backoff::Backoff;
+
+const THREADS: usize = 8;
+const INCREMENTS: usize = 10_000;
+
+/// increment `counter` from several threads with a CAS loop, calling `retry` after each failure
+fn contended_increments(retry: impl Fn(&Backoff) + Sync) {
+    let counter = AtomicUsize::new(0);
+
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|| {
+                for _ in 0..INCREMENTS {
+                    let backoff = Backoff::new();
+                    let mut current = counter.load(Ordering::Relaxed);
+                    while let Err(actual) = counter.compare_exchange_weak(
+                        current,
+                        current + 1,
+                        Ordering::AcqRel,
+                        Ordering::Relaxed,
+                    ) {
+                        current = actual;
+                        retry(&backoff);
+                    }
+                }
+            });
+        }
+    });
+
+    assert_eq!(counter.into_inner(), THREADS * INCREMENTS);
+}
+
+fn cas_loop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("contended_cas");
+    group.sample_size(10);
+
+    group.bench_function("no_backoff", |b| b.iter(|| contended_increments(|_| {})));
+    group.bench_function("backoff", |b| {
+        b.iter(|| contended_increments(Backoff::spin))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, cas_loop);
+criterion_main!(benches);
@@ -0,0 +1,42 @@
+//! Compare the same single-threaded algorithm on `LocalAtomic` and on `Atomic`.
+
+use std::{hint::black_box, sync::atomic::Ordering};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use STM::{
+    atomic::{Atomic, PointerCell},
+    local::LocalAtomic,
+};
+
+const SWAPS: usize = 10_000;
+
+/// compare-and-swap two boxes in and out of a cell, so the loop never allocates
+fn swap_boxes<C: PointerCell<usize>>() {
+    let cell = C::new(Some(Box::new(0)));
+    let mut spare = Box::new(1);
+
+    for _ in 0..SWAPS {
+        let current = black_box(cell.load(Ordering::Acquire));
+        match cell.compare_exchange(current, Some(spare), Ordering::AcqRel, Ordering::Acquire) {
+            // the cell is ours alone, so the replaced box can be taken back right away
+            Ok(old) => spare = unsafe { Box::from_raw(old) },
+            Err(_) => unreachable!("no other thread touches the cell"),
+        }
+    }
+
+    // neither cell frees its pointee
+    let (last, _) = cell.exchange_reporting(None, Ordering::AcqRel);
+    drop(last);
+}
+
+fn single_threaded(c: &mut Criterion) {
+    let mut group = c.benchmark_group("single_threaded_cas");
+
+    group.bench_function("atomic", |b| b.iter(swap_boxes::<Atomic<usize>>));
+    group.bench_function("local_atomic", |b| b.iter(swap_boxes::<LocalAtomic<usize>>));
+
+    group.finish();
+}
+
+criterion_group!(benches, single_threaded);
+criterion_main!(benches);
@@ -0,0 +1,56 @@
+//! Compare per-thread writes to adjacent atomics with and without cache-line padding.
+
+use std::{sync::atomic::Ordering, thread};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use STM::atomic::{Atomic, PaddedAtomic};
+
+const THREADS: usize = 4;
+const SWAPS: usize = 100_000;
+
+/// have each thread swap boxes in and out of its own slot, so any contention is false sharing
+fn swap_own_slot<S: Sync>(slots: &[S], atomic: impl Fn(&S) -> &Atomic<usize> + Sync) {
+    thread::scope(|s| {
+        for slot in slots {
+            let slot = atomic(slot);
+            s.spawn(move || {
+                // trade the same two boxes back and forth, so the loop never allocates
+                let mut spare = Box::new(0);
+                for _ in 0..SWAPS {
+                    let (old, _) = slot.exchange_reporting(Some(spare), Ordering::AcqRel);
+                    spare = old.unwrap();
+                }
+            });
+        }
+    });
+}
+
+fn adjacent_slots(c: &mut Criterion) {
+    let mut group = c.benchmark_group("adjacent_slots");
+    group.sample_size(10);
+
+    let plain: Vec<Atomic<usize>> = (0..THREADS)
+        .map(|_| Atomic::from_box(Box::new(0)))
+        .collect();
+    let padded: Vec<PaddedAtomic<usize>> = (0..THREADS)
+        .map(|_| PaddedAtomic::new(Some(Box::new(0))))
+        .collect();
+
+    group.bench_function("plain", |b| b.iter(|| swap_own_slot(&plain, |slot| slot)));
+    group.bench_function("padded", |b| {
+        b.iter(|| swap_own_slot(&padded, |slot| &**slot))
+    });
+
+    group.finish();
+
+    // Atomic does not free its pointee
+    for slot in plain {
+        drop(slot.try_into_box());
+    }
+    for slot in padded {
+        drop(slot.into_inner().try_into_box());
+    }
+}
+
+criterion_group!(benches, adjacent_slots);
+criterion_main!(benches);
@@ -0,0 +1,192 @@
+//! Drive a single `Atomic<Payload>` through a fuzzer-chosen sequence of operations and check
+//! that every payload that was constructed is dropped exactly once.
+//!
+//! Run with `cargo fuzz run atomic_ownership` from the repository root.
+
+#![no_main]
+
+use std::{
+    collections::HashSet,
+    ptr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use STM::atomic::Atomic;
+
+static CONSTRUCTED: AtomicUsize = AtomicUsize::new(0);
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+/// ids of the payloads currently alive, so a second drop of the same payload is caught
+static LIVE: Mutex<Option<HashSet<usize>>> = Mutex::new(None);
+
+/// a value that records its construction and destruction
+struct Payload {
+    id: usize,
+}
+
+impl Payload {
+    fn boxed() -> Box<Payload> {
+        let id = CONSTRUCTED.fetch_add(1, Ordering::Relaxed);
+        let inserted = LIVE
+            .lock()
+            .unwrap()
+            .get_or_insert_with(HashSet::new)
+            .insert(id);
+        assert!(inserted, "payload {} constructed twice", id);
+
+        Box::new(Payload { id })
+    }
+}
+
+impl Drop for Payload {
+    fn drop(&mut self) {
+        let removed = LIVE
+            .lock()
+            .unwrap()
+            .get_or_insert_with(HashSet::new)
+            .remove(&self.id);
+        assert!(removed, "payload {} dropped twice", self.id);
+
+        DROPPED.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    /// swap in a new value or null, dropping the old one
+    Exchange {
+        some: bool,
+    },
+    /// compare-and-swap against the current pointer, which succeeds
+    CompareExchangeCurrent {
+        some: bool,
+    },
+    /// compare-and-swap against a pointer that is not installed, which fails
+    CompareExchangeStale {
+        some: bool,
+    },
+    SetOnce,
+    GetOrInit,
+    CasLoop {
+        abort: bool,
+    },
+    /// borrow the pointee mutably, checking it is the one last installed
+    GetMut,
+}
+
+/// free a pointer the atomic handed back to us, if any
+fn free(ptr: *mut Payload) {
+    if !ptr.is_null() {
+        drop(unsafe { Box::from_raw(ptr) });
+    }
+}
+
+fn some_payload(some: bool) -> Option<Box<Payload>> {
+    if some {
+        Some(Payload::boxed())
+    } else {
+        None
+    }
+}
+
+fuzz_target!(|ops: Vec<Op>| {
+    CONSTRUCTED.store(0, Ordering::Relaxed);
+    DROPPED.store(0, Ordering::Relaxed);
+    *LIVE.lock().unwrap() = Some(HashSet::new());
+
+    let mut atomic: Atomic<Payload> = Atomic::new(None);
+
+    for op in ops {
+        match op {
+            Op::Exchange { some } => {
+                let (old, installed) =
+                    atomic.exchange_reporting(some_payload(some), Ordering::AcqRel);
+                assert_eq!(installed, atomic.load(Ordering::Acquire));
+                drop(old);
+            }
+            Op::CompareExchangeCurrent { some } => {
+                let current = atomic.load(Ordering::Acquire);
+                let old = atomic
+                    .compare_exchange(
+                        current,
+                        some_payload(some),
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                    )
+                    .unwrap_or_else(|_| {
+                        panic!("compare_exchange against the current pointer failed")
+                    });
+                assert_eq!(old, current);
+                free(old);
+            }
+            Op::CompareExchangeStale { some } => {
+                let current = atomic.load(Ordering::Acquire);
+                // a live payload's address can never be installed, as it was never published
+                let stale = Payload::boxed();
+                let stale_ptr = &*stale as *const Payload as *mut Payload;
+                match atomic.compare_exchange(
+                    stale_ptr,
+                    some_payload(some),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                ) {
+                    Ok(_) => panic!("compare_exchange against a stale pointer succeeded"),
+                    Err((rejected, actual)) => {
+                        assert_eq!(actual, current);
+                        assert_eq!(rejected.is_some(), some);
+                    }
+                }
+                assert_eq!(atomic.load(Ordering::Acquire), current);
+                drop(stale);
+            }
+            Op::SetOnce => {
+                let was_null = atomic.load(Ordering::Acquire).is_null();
+                let result = atomic.set_once(Payload::boxed());
+                assert_eq!(result.is_ok(), was_null);
+            }
+            Op::GetOrInit => {
+                let before = atomic.load(Ordering::Acquire);
+                let ptr = atomic.get_or_init(Payload::boxed);
+                assert!(!ptr.is_null());
+                assert!(before.is_null() || ptr::eq(ptr, before));
+            }
+            Op::CasLoop { abort } => {
+                let mut replaced = ptr::null_mut();
+                let installed = atomic.cas_loop(|current| {
+                    replaced = current;
+                    if abort {
+                        None
+                    } else {
+                        Some(Payload::boxed())
+                    }
+                });
+                assert_eq!(installed, atomic.load(Ordering::Acquire));
+                if !abort {
+                    // cas_loop leaves the replaced pointer to the caller
+                    free(replaced);
+                }
+            }
+            Op::GetMut => {
+                let current = atomic.load(Ordering::Relaxed);
+                match atomic.get_mut() {
+                    Some(payload) => assert!(ptr::eq(payload, current)),
+                    None => assert!(current.is_null()),
+                }
+            }
+        }
+    }
+
+    // Atomic does not free its pointee, so release whatever is still installed
+    drop(atomic.exchange_reporting(None, Ordering::AcqRel).0);
+
+    assert_eq!(
+        CONSTRUCTED.load(Ordering::Relaxed),
+        DROPPED.load(Ordering::Relaxed),
+        "payloads leaked"
+    );
+    assert!(LIVE.lock().unwrap().as_ref().unwrap().is_empty());
+});
@@ -1,12 +1,50 @@
-use std::ptr;
-use std::{marker::PhantomData, sync::atomic::AtomicPtr};
+use std::ptr::{self, NonNull};
+use std::{
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicPtr, AtomicU64, Ordering},
+};
 
+use crate::{
+    guard::{AtomicGuard, ProtectedRef},
+    hazard::Writer,
+};
+
+/// An atomic pointer to an owned, heap-allocated `T`, where null means no value.
+///
+/// # Zero-sized types
+///
+/// Boxing a zero-sized type allocates nothing, and [`Box::into_raw`] returns a dangling but
+/// non-null pointer, so `Atomic<()>` still tells a present value apart from null and freeing
+/// a box handed back by it never touches the allocator. Every box of the same zero-sized type
+/// has the same address, however, so [`Atomic::compare_exchange`] and [`Atomic::cas_loop`]
+/// cannot tell one such value from another.
+///
+/// # Thread safety
+///
+/// Any thread holding `&Atomic<T>` can take the boxed value out, e.g. with
+/// [`Atomic::exchange_reporting`], or read it under a hazard, so sharing the atomic requires
+/// `T: Send + Sync`. A value that must stay on its thread cannot be smuggled out:
+///
+/// ```compile_fail
+/// use std::sync::MutexGuard;
+/// use STM::atomic::Atomic;
+///
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<Atomic<MutexGuard<'static, i32>>>();
+/// ```
 pub struct Atomic<T> {
     /// inner atomic pointer
     inner: AtomicPtr<T>,
-    _marker: PhantomData<T>,
+    /// opts out of the auto traits, which `AtomicPtr` grants regardless of `T`
+    _marker: PhantomData<*mut T>,
 }
 
+// the atomic owns a `T` that any thread sharing it may take out or read
+unsafe impl<T: Send> Send for Atomic<T> {}
+unsafe impl<T: Send + Sync> Sync for Atomic<T> {}
+
 impl<T> Atomic<T> {
     pub fn new(init: Option<Box<T>>) -> Self {
         Self {
@@ -15,15 +53,631 @@ impl<T> Atomic<T> {
         }
     }
 
+    /// Create an atomic holding the given box, which is always non-null.
+    pub fn from_box(init: Box<T>) -> Self {
+        Self::new(Some(init))
+    }
+
     /// Get a mutable reference.
     ///
     /// `None` corresponds to a null pointer.
+    ///
+    /// # Safety
+    ///
+    /// The caller must not store a pointer that was not obtained from `Box::into_raw`.
     pub unsafe fn get_inner(&self) -> &AtomicPtr<T> {
         &self.inner
     }
 
     /// Get a mutable reference.
+    ///
+    /// # Safety
+    ///
+    /// The caller must not store a pointer that was not obtained from `Box::into_raw`.
     pub unsafe fn get_inner_mut(&mut self) -> &mut AtomicPtr<T> {
         &mut self.inner
     }
+
+    /// Load the current pointer.
+    ///
+    /// The pointee must not be dereferenced unless it is known to be alive,
+    /// e.g. because it was protected with [`Atomic::acquire`].
+    pub fn load(&self, order: Ordering) -> *mut T {
+        self.inner.load(order)
+    }
+
+    /// Store a raw pointer, for callers that manage allocation themselves.
+    ///
+    /// The previous pointer is overwritten without being returned or freed, so the caller
+    /// must already own its reclamation, e.g. by having retired it elsewhere.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null or valid for as long as it is installed. Methods that hand the
+    /// installed value back as a `Box`, such as [`Atomic::exchange_reporting`], may only see
+    /// it if it came from `Box::into_raw`; otherwise the caller must replace it with
+    /// `store_ptr` again before using them.
+    pub unsafe fn store_ptr(&self, ptr: *mut T, order: Ordering) {
+        self.inner.store(ptr, order);
+    }
+
+    /// Get a mutable reference to the pointee.
+    ///
+    /// `None` corresponds to a null pointer. Holding `&mut self` rules out any
+    /// concurrent access, so no synchronization is needed.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.inner.get_mut().as_mut() }
+    }
+
+    /// Consume the atomic and return its value, or `None` if it is null.
+    ///
+    /// Owning the atomic rules out any concurrent access, so no synchronization is needed.
+    pub fn try_into_box(self) -> Option<Box<T>> {
+        let ptr = self.inner.into_inner();
+        if ptr.is_null() {
+            None
+        } else {
+            Some(unsafe { Box::from_raw(ptr) })
+        }
+    }
+
+    /// Protect the current pointee with `writer` and return a reference to it.
+    ///
+    /// The pointer is loaded, protected, and loaded again until both loads agree, so the
+    /// protection was published while the pointer was still installed. A reclaimer that
+    /// checks the hazard after unlinking the pointer will then see it protected.
+    ///
+    /// Returns `None`, leaving the hazard free, if the atomic is null.
+    ///
+    /// # Safety
+    ///
+    /// The hazard only keeps the pointee alive if reclaimers honour it, which the type system
+    /// cannot check:
+    ///
+    /// - a pointer unlinked from this atomic must not be freed, nor turned back into a `Box`
+    ///   (which asserts unique access), while the [`Reader`] paired with `writer` may still
+    ///   report it as `State::Protect`. Unlink with a raw swap through
+    ///   [`Atomic::get_inner`] rather than [`Atomic::exchange_reporting`] if readers may be
+    ///   active;
+    /// - `writer` must not change the hazard state, including through another `acquire`,
+    ///   while the returned guard is alive, since that withdraws the guard's protection.
+    ///
+    /// [`Reader`]: crate::hazard::Reader
+    pub unsafe fn acquire<'a>(&'a self, writer: &'a Writer) -> Option<ProtectedRef<'a, T>> {
+        let ptr = writer.protect_from(|| self.inner.load(Ordering::Acquire));
+        NonNull::new(ptr).map(|ptr| ProtectedRef::new(ptr, writer))
+    }
+
+    /// Protect the current pointee with `writer` for as long as the returned guard lives.
+    ///
+    /// Unlike [`Atomic::acquire`], a null atomic still yields a guard, whose
+    /// [`get`](AtomicGuard::get) returns `None`. The hazard is set to free when the guard drops.
+    ///
+    /// # Safety
+    ///
+    /// The same as for [`Atomic::acquire`], for as long as the guard lives.
+    pub unsafe fn guard<'a>(&'a self, writer: &'a Writer) -> AtomicGuard<'a, T> {
+        AtomicGuard::new(self.acquire(writer))
+    }
+
+    /// Protect the current pointee with `writer`, pass it to `f`, and free the hazard.
+    ///
+    /// `f` receives `None` if the atomic is null. The reference cannot escape `f`, so it
+    /// never outlives the protection. Returns whatever `f` returns.
+    ///
+    /// # Safety
+    ///
+    /// The same as for [`Atomic::acquire`], until `f` returns. In particular `f` must not use
+    /// `writer`, e.g. to acquire another atomic, as that would drop the protection it reads under.
+    pub unsafe fn inspect<R>(&self, writer: &Writer, f: impl FnOnce(Option<&T>) -> R) -> R {
+        let protected = self.acquire(writer);
+        f(protected.as_deref())
+    }
+
+    /// Read two atomics and return a pair of pointers that were both installed at one instant.
+    ///
+    /// Uses a double collect: both atomics are read, then read again, and the loop retries until
+    /// the two reads agree. This is not a true atomic snapshot — an ABA change to either atomic
+    /// between the reads goes unnoticed — but it rules out the read skew of two plain loads,
+    /// where the second load sees a value installed after the first one was replaced.
+    pub fn snapshot2<U>(a: &Atomic<T>, b: &Atomic<U>) -> (*mut T, *mut U) {
+        let mut first = (a.load(Ordering::Acquire), b.load(Ordering::Acquire));
+
+        loop {
+            let second = (a.load(Ordering::Acquire), b.load(Ordering::Acquire));
+            if first == second {
+                return second;
+            }
+            first = second;
+        }
+    }
+
+    /// Swap in `new` and return the previous value along with the now-current pointer.
+    ///
+    /// The returned pointer is the address `new` was installed at, or null for `None`,
+    /// which saves hot loops a reload. The caller takes ownership of the previous box;
+    /// it must not free it while other threads may still be reading it.
+    pub fn exchange_reporting(
+        &self,
+        new: Option<Box<T>>,
+        order: Ordering,
+    ) -> (Option<Box<T>>, *mut T) {
+        let new = new.map_or(ptr::null_mut(), Box::into_raw);
+        let old = self.inner.swap(new, order);
+
+        let old = if old.is_null() {
+            None
+        } else {
+            Some(unsafe { Box::from_raw(old) })
+        };
+
+        (old, new)
+    }
+
+    /// Swap in `new` and return the previous value, for contended exchange loops.
+    ///
+    /// This is [`Atomic::exchange_reporting`] without the installed pointer, with the same
+    /// ownership rules for the previous box. A swap is a single wait-free read-modify-write on
+    /// every target the standard library supports, so there are no failed attempts to back
+    /// off from; [`Backoff`](crate::backoff::Backoff) is meant for the compare-and-swap loops
+    /// built on top of `Atomic`, such as [`Atomic::cas_loop`].
+    pub fn swap_with_backoff(&self, new: Option<Box<T>>, order: Ordering) -> Option<Box<T>> {
+        self.exchange_reporting(new, order).0
+    }
+
+    /// Install `new` if the atomic still holds `current`.
+    ///
+    /// On success returns the replaced pointer, which the caller now owns but must not free
+    /// while other threads may still be reading it. On failure the atomic is left untouched,
+    /// and the rejected box is handed back together with the pointer actually found.
+    pub fn compare_exchange(
+        &self,
+        current: *mut T,
+        new: Option<Box<T>>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<*mut T, (Option<Box<T>>, *mut T)> {
+        compare_exchange_box(&self.inner, current, new, success, failure)
+    }
+
+    /// Like [`Atomic::compare_exchange`], but reports the result as a [`CasOutcome`].
+    pub fn compare_exchange_outcome(
+        &self,
+        current: *mut T,
+        new: Option<Box<T>>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> CasOutcome<T> {
+        match self.compare_exchange(current, new, success, failure) {
+            Ok(old) => CasOutcome::Installed { old },
+            Err((rejected, current)) => CasOutcome::Failed { rejected, current },
+        }
+    }
+
+    /// Install `val` only if the atomic is still null, like a write-once cell.
+    ///
+    /// If another value was installed first, `val` is handed back untouched.
+    pub fn set_once(&self, val: Box<T>) -> Result<(), Box<T>> {
+        match self.compare_exchange(
+            ptr::null_mut(),
+            Some(val),
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => Ok(()),
+            Err((rejected, _)) => Err(rejected.unwrap()),
+        }
+    }
+
+    /// Return the installed pointer, initializing the atomic with `init` if it is null.
+    ///
+    /// Several threads may run `init` concurrently, but only one result is installed;
+    /// the losers free their own boxes and return the winner's pointer. Once installed,
+    /// the value is never replaced by this method, so the pointer stays valid for as long
+    /// as nothing else swaps it out.
+    pub fn get_or_init(&self, init: impl FnOnce() -> Box<T>) -> *mut T {
+        let current = self.inner.load(Ordering::Acquire);
+        if !current.is_null() {
+            return current;
+        }
+
+        // publish the raw pointer itself, so the one returned keeps the box's full provenance
+        let new = Box::into_raw(init());
+
+        match self
+            .inner
+            .compare_exchange(ptr::null_mut(), new, Ordering::AcqRel, Ordering::Acquire)
+        {
+            Ok(_) => new,
+            Err(actual) => {
+                drop(unsafe { Box::from_raw(new) });
+                actual
+            }
+        }
+    }
+
+    /// Repeatedly replace the current pointer with one computed from it.
+    ///
+    /// Each iteration loads the current pointer and passes it to `make_new`.
+    /// Returning `None` aborts the loop and leaves the atomic unchanged.
+    /// Otherwise the new box is installed with a compare-and-swap; if another
+    /// thread got there first, the rejected box is freed and the loop retries.
+    ///
+    /// Returns the pointer that was finally installed, or the current pointer if aborted.
+    /// The replaced pointer is not freed, since other threads may still be reading it.
+    pub fn cas_loop(&self, mut make_new: impl FnMut(*mut T) -> Option<Box<T>>) -> *mut T {
+        let mut current = self.inner.load(Ordering::Acquire);
+
+        loop {
+            let new = match make_new(current) {
+                Some(new) => Box::into_raw(new),
+                None => return current,
+            };
+
+            match self
+                .inner
+                .compare_exchange(current, new, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => return new,
+                Err(actual) => {
+                    // the box was never published, so it is still exclusively ours
+                    drop(unsafe { Box::from_raw(new) });
+                    current = actual;
+                }
+            }
+        }
+    }
+}
+
+/// install the box `new` in `cell` if it still holds `current`, handing it back otherwise
+fn compare_exchange_box<T>(
+    cell: &AtomicPtr<T>,
+    current: *mut T,
+    new: Option<Box<T>>,
+    success: Ordering,
+    failure: Ordering,
+) -> Result<*mut T, (Option<Box<T>>, *mut T)> {
+    let new = new.map_or(ptr::null_mut(), Box::into_raw);
+
+    match cell.compare_exchange(current, new, success, failure) {
+        Ok(old) => Ok(old),
+        Err(actual) => {
+            // the box was never published, so it is still exclusively ours
+            let rejected = if new.is_null() {
+                None
+            } else {
+                Some(unsafe { Box::from_raw(new) })
+            };
+            Err((rejected, actual))
+        }
+    }
+}
+
+/// The pointer-cell operations shared by [`Atomic<T>`] and [`LocalAtomic<T>`](crate::local::LocalAtomic).
+///
+/// Data structures generic over this trait can run on `Atomic` when shared between threads,
+/// and on the synchronization-free `LocalAtomic` when confined to one.
+/// `LocalAtomic` ignores the orderings.
+pub trait PointerCell<T> {
+    fn new(init: Option<Box<T>>) -> Self
+    where
+        Self: Sized;
+
+    fn load(&self, order: Ordering) -> *mut T;
+
+    fn exchange_reporting(&self, new: Option<Box<T>>, order: Ordering) -> (Option<Box<T>>, *mut T);
+
+    fn compare_exchange(
+        &self,
+        current: *mut T,
+        new: Option<Box<T>>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<*mut T, (Option<Box<T>>, *mut T)>;
+
+    fn cas_loop(&self, make_new: impl FnMut(*mut T) -> Option<Box<T>>) -> *mut T;
+}
+
+impl<T> PointerCell<T> for Atomic<T> {
+    fn new(init: Option<Box<T>>) -> Self {
+        Atomic::new(init)
+    }
+
+    fn load(&self, order: Ordering) -> *mut T {
+        Atomic::load(self, order)
+    }
+
+    fn exchange_reporting(&self, new: Option<Box<T>>, order: Ordering) -> (Option<Box<T>>, *mut T) {
+        Atomic::exchange_reporting(self, new, order)
+    }
+
+    fn compare_exchange(
+        &self,
+        current: *mut T,
+        new: Option<Box<T>>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<*mut T, (Option<Box<T>>, *mut T)> {
+        Atomic::compare_exchange(self, current, new, success, failure)
+    }
+
+    fn cas_loop(&self, make_new: impl FnMut(*mut T) -> Option<Box<T>>) -> *mut T {
+        Atomic::cas_loop(self, make_new)
+    }
+}
+
+/// The result of [`Atomic::compare_exchange_outcome`].
+#[derive(Debug)]
+pub enum CasOutcome<T> {
+    /// the new value was installed, replacing `old`
+    Installed { old: *mut T },
+    /// the atomic held `current` instead of the expected pointer, so `rejected` was not installed
+    Failed {
+        rejected: Option<Box<T>>,
+        current: *mut T,
+    },
+}
+
+impl<T> From<Box<T>> for Atomic<T> {
+    fn from(init: Box<T>) -> Self {
+        Self::from_box(init)
+    }
+}
+
+// `TryFrom<Atomic<T>> for Box<T>` is ruled out by the orphan rules, as `Box` is fundamental
+impl<T> From<Atomic<T>> for Option<Box<T>> {
+    fn from(atomic: Atomic<T>) -> Self {
+        atomic.try_into_box()
+    }
+}
+
+/// An [`Atomic<T>`] aligned to 128 bytes so that it never shares a cache line with its neighbours.
+///
+/// Two lines rather than one also defeats adjacent-line prefetching on common x86 parts.
+/// Use it for arrays of per-slot atomics that are written from different threads; the plain
+/// `Atomic<T>` stays a single word for memory-tight layouts.
+#[repr(align(128))]
+pub struct PaddedAtomic<T> {
+    inner: Atomic<T>,
+}
+
+impl<T> PaddedAtomic<T> {
+    pub fn new(init: Option<Box<T>>) -> Self {
+        Self {
+            inner: Atomic::new(init),
+        }
+    }
+
+    /// Unwrap the padded atomic.
+    pub fn into_inner(self) -> Atomic<T> {
+        self.inner
+    }
+}
+
+impl<T> From<Atomic<T>> for PaddedAtomic<T> {
+    fn from(inner: Atomic<T>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T> Deref for PaddedAtomic<T> {
+    type Target = Atomic<T>;
+
+    fn deref(&self) -> &Atomic<T> {
+        &self.inner
+    }
+}
+
+impl<T> DerefMut for PaddedAtomic<T> {
+    fn deref_mut(&mut self) -> &mut Atomic<T> {
+        &mut self.inner
+    }
+}
+
+/// A fixed-length array of atomic pointers to owned `T`s, the backing store of slot-based
+/// structures such as ring buffers and hash tables.
+///
+/// Each slot follows the same rules as an [`Atomic<T>`]: null means empty, and a non-null
+/// slot owns a box. Unlike `Atomic<T>`, the array frees the boxes still installed when it drops.
+///
+/// Like `Atomic<T>`, sharing the array requires `T: Send + Sync`, since any thread can store a
+/// box that the owning thread later frees:
+///
+/// ```compile_fail
+/// use std::sync::MutexGuard;
+/// use STM::atomic::AtomicArray;
+///
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<AtomicArray<MutexGuard<'static, i32>>>();
+/// ```
+pub struct AtomicArray<T> {
+    slots: Box<[AtomicPtr<T>]>,
+    /// opts out of the auto traits, which `AtomicPtr` grants regardless of `T`
+    _marker: PhantomData<*mut T>,
+}
+
+// the array owns the `T`s that any thread sharing it may store, take out or read
+unsafe impl<T: Send> Send for AtomicArray<T> {}
+unsafe impl<T: Send + Sync> Sync for AtomicArray<T> {}
+
+impl<T> AtomicArray<T> {
+    /// Create an array of `len` empty slots.
+    pub fn new(len: usize) -> Self {
+        Self {
+            slots: (0..len).map(|_| AtomicPtr::new(ptr::null_mut())).collect(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Load the pointer in slot `idx`.
+    ///
+    /// The pointee must not be dereferenced unless it is known to be alive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn load(&self, idx: usize, order: Ordering) -> *mut T {
+        self.slots[idx].load(order)
+    }
+
+    /// Install `val` in slot `idx` and return the pointer it replaced.
+    ///
+    /// The caller takes ownership of the replaced pointer, but must not free it, or turn it
+    /// back into a `Box`, while other threads may still be reading it. It is returned raw so
+    /// the caller can wait those readers out first; ignoring it leaks the old value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    #[must_use = "the replaced pointer is owned by the caller and leaks if ignored"]
+    pub fn store(&self, idx: usize, val: Option<Box<T>>, order: Ordering) -> *mut T {
+        let val = val.map_or(ptr::null_mut(), Box::into_raw);
+        self.slots[idx].swap(val, order)
+    }
+
+    /// Install `new` in slot `idx` if it still holds `current`.
+    ///
+    /// Behaves like [`Atomic::compare_exchange`] on the slot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn compare_exchange(
+        &self,
+        idx: usize,
+        current: *mut T,
+        new: Option<Box<T>>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<*mut T, (Option<Box<T>>, *mut T)> {
+        compare_exchange_box(&self.slots[idx], current, new, success, failure)
+    }
+
+    /// Get slot `idx` without a bounds check.
+    ///
+    /// # Safety
+    ///
+    /// `idx` must be less than [`AtomicArray::len`], and the caller must not store a pointer
+    /// that was not obtained from `Box::into_raw`.
+    pub unsafe fn get_unchecked(&self, idx: usize) -> &AtomicPtr<T> {
+        self.slots.get_unchecked(idx)
+    }
+}
+
+impl<T> Drop for AtomicArray<T> {
+    fn drop(&mut self) {
+        for slot in self.slots.iter_mut() {
+            let ptr = *slot.get_mut();
+            if !ptr.is_null() {
+                drop(unsafe { Box::from_raw(ptr) });
+            }
+        }
+    }
+}
+
+/// Types that [`AtomicValue`] can hold: plain `Copy` data whose every byte is initialized.
+///
+/// # Safety
+///
+/// The type must have no padding bytes, since its bytes are copied into an integer and
+/// compared bit for bit, and it must fit in a `u64`.
+pub unsafe trait AtomicBits: Copy {}
+
+macro_rules! atomic_bits {
+    ($($ty:ty),*) => {
+        $(unsafe impl AtomicBits for $ty {})*
+    };
+}
+
+atomic_bits!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64, bool, char);
+
+/// An atomic cell holding a small `Copy` value inline, with no heap allocation.
+///
+/// This complements [`Atomic<T>`] for scalars, where boxing every value would be wasteful.
+/// The value is stored in an `AtomicU64`, so `T` must fit in 8 bytes; larger types fail to
+/// compile. [`AtomicValue::compare_exchange`] compares bit patterns, so for floats `0.0` and
+/// `-0.0` differ and a `NaN` can match itself.
+///
+/// ```compile_fail
+/// use STM::atomic::{AtomicBits, AtomicValue};
+///
+/// #[derive(Clone, Copy)]
+/// struct Wide(u64, u64);
+/// unsafe impl AtomicBits for Wide {}
+///
+/// let _ = AtomicValue::new(Wide(1, 2));
+/// ```
+#[derive(Debug)]
+pub struct AtomicValue<T: AtomicBits> {
+    bits: AtomicU64,
+    _marker: PhantomData<T>,
+}
+
+impl<T: AtomicBits> AtomicValue<T> {
+    const FITS: () = assert!(
+        mem::size_of::<T>() <= mem::size_of::<u64>(),
+        "AtomicValue requires a type of at most 8 bytes"
+    );
+
+    pub fn new(val: T) -> Self {
+        let () = Self::FITS;
+
+        Self {
+            bits: AtomicU64::new(Self::to_bits(val)),
+            _marker: PhantomData,
+        }
+    }
+
+    fn to_bits(val: T) -> u64 {
+        let mut bits = 0u64;
+        // `T` fits in and is no more aligned than a `u64`, and has no padding
+        unsafe { ptr::write(&mut bits as *mut u64 as *mut T, val) };
+        bits
+    }
+
+    fn from_bits(bits: u64) -> T {
+        // every stored bit pattern was produced by `to_bits` from a valid `T`
+        unsafe { ptr::read(&bits as *const u64 as *const T) }
+    }
+
+    pub fn load(&self, order: Ordering) -> T {
+        Self::from_bits(self.bits.load(order))
+    }
+
+    pub fn store(&self, val: T, order: Ordering) {
+        self.bits.store(Self::to_bits(val), order);
+    }
+
+    /// Store `val` and return the previous value.
+    pub fn swap(&self, val: T, order: Ordering) -> T {
+        Self::from_bits(self.bits.swap(Self::to_bits(val), order))
+    }
+
+    /// Store `new` if the cell still holds `current`, comparing bit patterns.
+    ///
+    /// Returns the previous value on success, and the value actually found on failure.
+    pub fn compare_exchange(
+        &self,
+        current: T,
+        new: T,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<T, T> {
+        self.bits
+            .compare_exchange(Self::to_bits(current), Self::to_bits(new), success, failure)
+            .map(Self::from_bits)
+            .map_err(Self::from_bits)
+    }
+
+    pub fn into_inner(self) -> T {
+        Self::from_bits(self.bits.into_inner())
+    }
 }
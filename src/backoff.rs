@@ -0,0 +1,80 @@
+//! Exponential backoff for CAS loops.
+
+use std::{cell::Cell, hint, thread};
+
+/// doubling the spin count beyond `1 << SPIN_LIMIT` stops paying off
+const SPIN_LIMIT: u32 = 6;
+/// after this many steps `snooze` stops escalating, see [`Backoff::is_completed`]
+const YIELD_LIMIT: u32 = 10;
+
+/// Exponential backoff for retry loops on contended atomics.
+///
+/// Each failed attempt backs off twice as long as the previous one, so threads hammering the
+/// same cache line spread out instead of invalidating it for each other.
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use STM::backoff::Backoff;
+///
+/// let counter = AtomicUsize::new(0);
+/// let backoff = Backoff::new();
+/// let mut current = counter.load(Ordering::Relaxed);
+/// while let Err(actual) =
+///     counter.compare_exchange_weak(current, current + 1, Ordering::AcqRel, Ordering::Relaxed)
+/// {
+///     current = actual;
+///     backoff.spin();
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct Backoff {
+    step: Cell<u32>,
+}
+
+impl Backoff {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start over from the shortest backoff.
+    pub fn reset(&self) {
+        self.step.set(0);
+    }
+
+    /// Back off after a failed attempt in a lock-free loop.
+    ///
+    /// Only ever spins, since the other thread is making progress and will be done soon.
+    pub fn spin(&self) {
+        for _ in 0..1 << self.step.get().min(SPIN_LIMIT) {
+            hint::spin_loop();
+        }
+
+        if self.step.get() <= SPIN_LIMIT {
+            self.step.set(self.step.get() + 1);
+        }
+    }
+
+    /// Back off while waiting for another thread to make progress.
+    ///
+    /// Spins at first, then yields the thread once spinning stops paying off.
+    pub fn snooze(&self) {
+        if self.step.get() <= SPIN_LIMIT {
+            for _ in 0..1 << self.step.get() {
+                hint::spin_loop();
+            }
+        } else {
+            thread::yield_now();
+        }
+
+        if self.step.get() <= YIELD_LIMIT {
+            self.step.set(self.step.get() + 1);
+        }
+    }
+
+    /// Whether [`Backoff::snooze`] has escalated as far as it goes.
+    ///
+    /// Waiting loops can use this to switch to blocking, e.g. parking the thread.
+    pub fn is_completed(&self) -> bool {
+        self.step.get() > YIELD_LIMIT
+    }
+}
@@ -0,0 +1,147 @@
+//! Fixed-size hazard domains that need no heap allocation.
+
+use core::{
+    ptr,
+    sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering},
+};
+
+/// a single hazard slot of a [`StaticHazardDomain`]
+#[derive(Debug)]
+struct DomainSlot {
+    /// whether a shield currently owns this slot
+    owned: AtomicBool,
+    /// the protected pointer, or null if the slot protects nothing
+    protected: AtomicPtr<u8>,
+    /// how many times this slot has been acquired
+    acquisitions: AtomicUsize,
+}
+
+impl DomainSlot {
+    const fn new() -> Self {
+        Self {
+            owned: AtomicBool::new(false),
+            protected: AtomicPtr::new(ptr::null_mut()),
+            acquisitions: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// A hazard domain with exactly `N` slots stored inline.
+///
+/// Nothing is allocated, so the domain can be placed in a `static` and used where there is no
+/// allocator, e.g. on embedded targets with a known, fixed number of threads.
+/// At most `N` shields can be held at the same time.
+///
+/// ```
+/// use STM::domain::StaticHazardDomain;
+///
+/// static DOMAIN: StaticHazardDomain<2> = StaticHazardDomain::new();
+///
+/// let shield = DOMAIN.acquire().unwrap();
+/// let x = 1u8;
+/// shield.protect(&x);
+/// assert!(DOMAIN.is_protected(&x));
+/// ```
+#[derive(Debug)]
+pub struct StaticHazardDomain<const N: usize> {
+    slots: [DomainSlot; N],
+}
+
+impl<const N: usize> StaticHazardDomain<N> {
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { DomainSlot::new() }; N],
+        }
+    }
+
+    /// The number of slots, i.e. the maximum number of shields held at once.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Take ownership of an unused slot.
+    ///
+    /// Returns `None` if all `N` slots are currently held.
+    pub fn acquire(&self) -> Option<StaticShield<'_>> {
+        self.slots.iter().find_map(|slot| {
+            slot.owned
+                .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .ok()
+                .map(|_| {
+                    slot.acquisitions.fetch_add(1, Ordering::Relaxed);
+                    StaticShield { slot }
+                })
+        })
+    }
+
+    /// The number of times any slot has been acquired since the domain was created.
+    ///
+    /// Compared against the number of operations performed, a high count means shields are
+    /// acquired and released per operation and would be worth caching instead.
+    pub fn total_acquisitions(&self) -> usize {
+        self.slots
+            .iter()
+            .map(|slot| slot.acquisitions.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// Whether any slot currently protects `ptr`.
+    ///
+    /// A reclaimer must check this, after unlinking `ptr`, before freeing it.
+    pub fn is_protected(&self, ptr: *const u8) -> bool {
+        !ptr.is_null()
+            && self
+                .slots
+                .iter()
+                .any(|slot| ptr::eq(slot.protected.load(Ordering::SeqCst), ptr))
+    }
+
+    /// Check that no slot protects `ptr` before it is freed manually.
+    ///
+    /// Returns `true` if `ptr` is safe to free.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if `ptr` is currently protected, turning a would-be
+    /// use-after-free into a loud, local failure. Release builds only return `false`.
+    pub fn assert_not_protected(&self, ptr: *const u8) -> bool {
+        let protected = self.is_protected(ptr);
+        debug_assert!(!protected, "freeing {:p} while it is hazard-protected", ptr);
+        !protected
+    }
+}
+
+impl<const N: usize> Default for StaticHazardDomain<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Exclusive use of one slot of a [`StaticHazardDomain`].
+///
+/// Dropping the shield clears its protection and returns the slot to the domain.
+#[derive(Debug)]
+pub struct StaticShield<'a> {
+    slot: &'a DomainSlot,
+}
+
+impl StaticShield<'_> {
+    /// protect a pointer; protecting null is the same as [`StaticShield::free`]
+    pub fn protect(&self, ptr: *const u8) {
+        self.slot.protected.store(ptr as *mut u8, Ordering::SeqCst);
+    }
+
+    /// stop protecting any pointer
+    pub fn free(&self) {
+        self.slot
+            .protected
+            .store(ptr::null_mut(), Ordering::Release);
+    }
+}
+
+impl Drop for StaticShield<'_> {
+    fn drop(&mut self) {
+        self.free();
+        self.slot.owned.store(false, Ordering::Release);
+    }
+}
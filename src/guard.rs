@@ -1 +1,131 @@
+use std::{ops::Deref, ptr::NonNull, sync::atomic::Ordering};
 
+use crate::{atomic::Atomic, hazard::Writer};
+
+/// A reference to an object kept alive by a hazard protection.
+///
+/// Returned by [`Atomic::acquire`](crate::atomic::Atomic::acquire). The hazard is set to free
+/// when the guard is dropped, and the borrow checker ties every reference obtained through the
+/// guard to the guard itself, so none of them can outlive the protection.
+///
+/// ```compile_fail
+/// use STM::{atomic::Atomic, hazard::create};
+///
+/// let atomic = Atomic::from_box(Box::new(1));
+/// let (_r, w) = create();
+///
+/// let escaped: &i32 = {
+///     let guard = unsafe { atomic.acquire(&w) }.unwrap();
+///     &*guard
+/// };
+/// ```
+#[derive(Debug)]
+pub struct ProtectedRef<'a, T> {
+    ptr: NonNull<T>,
+    writer: &'a Writer,
+}
+
+impl<'a, T> ProtectedRef<'a, T> {
+    /// Wrap a pointer that `writer` currently protects.
+    ///
+    /// # Safety
+    ///
+    /// `writer` must protect `ptr`, and `ptr` must point to a valid `T` for as long as it does.
+    pub(crate) unsafe fn new(ptr: NonNull<T>, writer: &'a Writer) -> Self {
+        Self { ptr, writer }
+    }
+
+    /// The protected pointer.
+    pub fn as_ptr(&self) -> *mut T {
+        self.ptr.as_ptr()
+    }
+}
+
+impl<T> Deref for ProtectedRef<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T> Drop for ProtectedRef<'_, T> {
+    fn drop(&mut self) {
+        self.writer.free();
+    }
+}
+
+/// The hazard-protected contents of an [`Atomic`], which may be null.
+///
+/// Returned by [`Atomic::guard`](crate::atomic::Atomic::guard). The protection lasts until the
+/// guard is dropped, so it can span several statements. References obtained through
+/// [`AtomicGuard::get`] borrow the guard and cannot outlive it.
+#[derive(Debug)]
+pub struct AtomicGuard<'a, T> {
+    inner: Option<ProtectedRef<'a, T>>,
+}
+
+impl<'a, T> AtomicGuard<'a, T> {
+    pub(crate) fn new(inner: Option<ProtectedRef<'a, T>>) -> Self {
+        Self { inner }
+    }
+
+    /// The protected object, or `None` if the atomic was null.
+    pub fn get(&self) -> Option<&T> {
+        self.inner.as_deref()
+    }
+
+    /// Whether the atomic was null when the guard was taken.
+    pub fn is_none(&self) -> bool {
+        self.inner.is_none()
+    }
+}
+
+/// A hazard protection bound to the [`Atomic`] it was taken from, which can be re-validated.
+///
+/// Returned by [`Writer::pin_to`]. [`Pin::refresh`] re-runs the acquire protocol against the
+/// same source, so a long-lived read can follow a moving target without rebuilding the guard.
+/// Refreshing takes `&mut self`, so no reference to the previous object survives it.
+/// The hazard is set to free when the pin is dropped.
+pub struct Pin<'a, T> {
+    src: &'a Atomic<T>,
+    writer: &'a Writer,
+    ptr: *mut T,
+}
+
+impl<'a, T> Pin<'a, T> {
+    pub(crate) fn new(src: &'a Atomic<T>, writer: &'a Writer) -> Self {
+        let mut pin = Self {
+            src,
+            writer,
+            ptr: std::ptr::null_mut(),
+        };
+        pin.refresh();
+        pin
+    }
+
+    /// The protected object, or `None` if the source was null when last acquired.
+    pub fn get(&self) -> Option<&T> {
+        // the writer protects `ptr` until the next refresh or drop, both of which need `&mut self`
+        unsafe { self.ptr.as_ref() }
+    }
+
+    /// The protected pointer, which is null if the source was null when last acquired.
+    pub fn as_ptr(&self) -> *mut T {
+        self.ptr
+    }
+
+    /// Protect whatever the source holds now, and return it.
+    pub fn refresh(&mut self) -> Option<&T> {
+        self.ptr = self
+            .writer
+            .protect_from(|| self.src.load(Ordering::Acquire));
+        self.get()
+    }
+}
+
+impl<T> Drop for Pin<'_, T> {
+    fn drop(&mut self) {
+        self.writer.free();
+    }
+}
@@ -1,17 +1,40 @@
 use std::{
-    mem,
-    sync::atomic::{AtomicPtr, Ordering},
+    fmt, hint,
+    mem::{self, ManuallyDrop},
+    panic,
+    ptr::{self, NonNull},
+    sync::atomic::{self, AtomicBool, AtomicPtr, AtomicUsize, Ordering},
     thread,
+    time::{Duration, Instant},
 };
 
+use crate::{atomic::Atomic, guard::Pin};
+
+/// log a hazard protocol event when the `trace` feature is enabled, otherwise expand to nothing
+#[cfg(feature = "trace")]
+macro_rules! trace {
+    ($($arg:tt)*) => { log::trace!($($arg)*) };
+}
+
+#[cfg(not(feature = "trace"))]
+macro_rules! trace {
+    ($($arg:tt)*) => {};
+}
+
 static BLOCKED: u8 = 0x01;
 static FREE: u8 = 0x02;
 static DEAD: u8 = 0x03;
+static INVALIDATED: u8 = 0x04;
 
-#[derive(Debug, PartialEq)]
+#[derive(PartialEq)]
 pub enum State {
     /// hazard pointer does not protect any object
     Free,
+    /// the writer is changing the state, and readers wait until it is done
+    ///
+    /// [`Reader::get`] never returns this; it is only seen as the prior state
+    /// reported by [`Writer::swap_state`].
+    Blocked,
     /// when object is dead, it does not need to be protected
     /// also, can be deallocated if needed
     ///
@@ -19,12 +42,156 @@ pub enum State {
     /// For instance, altering its state or similar actions should be avoided,
     /// as its behavior is not clearly defined and it might have already been deallocated.
     Dead,
+    /// the protection was withdrawn by [`Writer::invalidate_protection`]
+    ///
+    /// The object that was protected is being reclaimed, so the reader must re-acquire
+    /// rather than use it. Until then the hazard protects nothing, like `Free`.
+    Invalidated,
     /// hazard pointer protects a object
     ///
     /// `Protect` ensures that the pointer it refers to isn't deleted while the hazard remains in this state
     Protect(*const u8),
 }
 
+impl State {
+    /// a hazard that is in the protecting state but protects nothing
+    ///
+    /// Writing `Protect(null)` is usually a bug (see [`Writer::protect_nonnull`]), so this gives
+    /// the state a name in tests and match arms. It compares equal to `State::Protect(ptr::null())`.
+    pub const PROTECT_NULL: State = State::Protect(ptr::null());
+}
+
+impl fmt::Debug for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            State::Free => write!(f, "Free"),
+            State::Blocked => write!(f, "Blocked"),
+            State::Dead => write!(f, "Dead"),
+            State::Invalidated => write!(f, "Invalidated"),
+            State::Protect(ptr) if ptr.is_null() => write!(f, "Protect(null)"),
+            State::Protect(ptr) => write!(f, "Protect({:p})", *ptr),
+        }
+    }
+}
+
+/// the allocation shared by both halves of a hazard pair
+#[derive(Debug)]
+struct Slot {
+    /// the current state, encoded
+    state: AtomicPtr<u8>,
+    /// the state the writer announced it will move to once unblocked,
+    /// or `BLOCKED` if nothing is announced
+    next: AtomicPtr<u8>,
+    /// whether the writer is still alive, i.e. neither killed nor dropped,
+    /// so `Reader::destroy` can name the teardown order it violates
+    #[cfg(debug_assertions)]
+    writer_alive: AtomicBool,
+}
+
+/// The address of the sentinel word that encodes [`State::Blocked`].
+///
+/// The sentinel addresses are stable for the life of the process, so external tools can
+/// decode a raw word, e.g. from [`Reader::observe_raw`], by comparing against them.
+pub fn blocked_sentinel() -> *const u8 {
+    &BLOCKED
+}
+
+/// The address of the sentinel word that encodes [`State::Free`].
+///
+/// Compare it against [`Reader::observe_raw`] to recognize a free hazard without decoding.
+pub fn free_sentinel() -> *const u8 {
+    &FREE
+}
+
+/// The address of the sentinel word that encodes [`State::Dead`].
+pub fn dead_sentinel() -> *const u8 {
+    &DEAD
+}
+
+/// The address of the sentinel word that encodes [`State::Invalidated`].
+pub fn invalidated_sentinel() -> *const u8 {
+    &INVALIDATED
+}
+
+/// The addresses of all sentinel words, bundled so tests take them from one place.
+///
+/// Only available in tests and with the `testing` feature. Returned by [`sentinels`].
+#[cfg(any(test, feature = "testing"))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sentinels {
+    pub blocked: *const u8,
+    pub free: *const u8,
+    pub dead: *const u8,
+    pub invalidated: *const u8,
+}
+
+/// Get the addresses of all sentinel words.
+#[cfg(any(test, feature = "testing"))]
+pub fn sentinels() -> Sentinels {
+    Sentinels {
+        blocked: blocked_sentinel(),
+        free: free_sentinel(),
+        dead: dead_sentinel(),
+        invalidated: invalidated_sentinel(),
+    }
+}
+
+/// whether an encoded state is one of the sentinels rather than a protected pointer
+fn is_sentinel(ptr: *const u8) -> bool {
+    ptr::eq(ptr, &BLOCKED)
+        || ptr::eq(ptr, &FREE)
+        || ptr::eq(ptr, &DEAD)
+        || ptr::eq(ptr, &INVALIDATED)
+}
+
+/// the word that protects `ptr`
+///
+/// # Panics
+///
+/// Panics if `ptr` is a sentinel address. It would be read back as another state, and a
+/// protection read as dead lets the reader free the slot under a live writer.
+fn protect_word(ptr: *const u8) -> *const u8 {
+    assert!(!is_sentinel(ptr), "cannot protect a sentinel address");
+    ptr
+}
+
+/// decode a state word
+fn decode(ptr: *const u8) -> State {
+    if ptr::eq(ptr, &BLOCKED) {
+        State::Blocked
+    } else if ptr::eq(ptr, &FREE) {
+        State::Free
+    } else if ptr::eq(ptr, &DEAD) {
+        State::Dead
+    } else if ptr::eq(ptr, &INVALIDATED) {
+        State::Invalidated
+    } else {
+        State::Protect(ptr)
+    }
+}
+
+/// encode a state into the word stored in the shared allocation
+fn encode(state: State) -> *const u8 {
+    match state {
+        State::Free => &FREE,
+        State::Blocked => &BLOCKED,
+        State::Dead => &DEAD,
+        State::Invalidated => &INVALIDATED,
+        State::Protect(ptr) => protect_word(ptr),
+    }
+}
+
+/// human readable form of an encoded state word, for tracing
+#[cfg(feature = "trace")]
+struct Word(*const u8);
+
+#[cfg(feature = "trace")]
+impl fmt::Debug for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&decode(self.0), f)
+    }
+}
+
 /// Instantiate a new hazard reader-writer pair.
 ///
 /// This action generates a new hazard pair in a blocked state.
@@ -34,110 +201,1019 @@ pub enum State {
 /// Additionally, there's a 'Blocked' state. When the hazard is in this state,
 /// any read operation will be on hold until it's unblocked.
 pub fn create() -> (Reader, Writer) {
-    let ptr = unsafe {
-        Box::into_raw(Box::new(AtomicPtr::new(&BLOCKED as *const u8 as *mut u8)))
-            .as_ref()
-            .unwrap()
-    };
+    create_pair(false)
+}
+
+/// Instantiate a new hazard reader-writer pair using sequentially consistent ordering.
+///
+/// The pair behaves exactly like one returned by [`create`], except every load, store and
+/// compare-and-swap on the hazard uses `Ordering::SeqCst` instead of acquire/release.
+///
+/// With acquire/release a reader only synchronizes with the writer whose store it observes,
+/// so two threads that each publish on one hazard and then read another may both miss each
+/// other's store. With `SeqCst` all operations on all such hazards fall into a single total
+/// order that every thread agrees on, so at least one of them sees the other's store.
+/// This is slower, and is meant for checking algorithms against a sequentially consistent
+/// model or for ruling out ordering bugs while debugging.
+pub fn create_seqcst() -> (Reader, Writer) {
+    create_pair(true)
+}
+
+fn create_pair(seqcst: bool) -> (Reader, Writer) {
+    let ptr = NonNull::from(Box::leak(Box::new(Slot {
+        state: AtomicPtr::new(&BLOCKED as *const u8 as *mut u8),
+        next: AtomicPtr::new(&BLOCKED as *const u8 as *mut u8),
+        #[cfg(debug_assertions)]
+        writer_alive: AtomicBool::new(true),
+    })));
 
-    let reader = Reader { ptr };
-    let writer = Writer { ptr };
+    let reader = Reader {
+        ptr,
+        seqcst,
+        policy: None,
+        spins: AtomicUsize::new(0),
+        ever_unblocked: AtomicBool::new(false),
+    };
+    let writer = Writer { ptr, seqcst };
 
     (reader, writer)
 }
 
+/// Start configuring a hazard pair, for users creating many pairs with the same wait policy.
+///
+/// ```
+/// use STM::hazard::{builder, ThreadParker};
+///
+/// let (r, w) = builder().spin_limit(16).parker(ThreadParker::default()).build();
+/// # w.kill();
+/// # unsafe { r.destroy() };
+/// ```
+pub fn builder() -> HazardBuilder {
+    HazardBuilder::default()
+}
+
+/// Configures how the reader of a new hazard pair waits out a blocked hazard.
+///
+/// Created by [`builder`]. The policy is stored in the [`Reader`], and its [`Reader::get`]
+/// follows it instead of busy-waiting.
+#[derive(Default)]
+pub struct HazardBuilder {
+    spin_limit: Option<usize>,
+    parker: Option<Box<dyn Parker + Send + Sync>>,
+    seqcst: bool,
+}
+
+impl HazardBuilder {
+    /// Park after the reader has found the hazard blocked `spin_limit` times.
+    ///
+    /// Without a [`HazardBuilder::parker`], parking yields the thread like [`ThreadParker`].
+    /// With one, this overrides its [`Parker::should_park`].
+    pub fn spin_limit(mut self, spin_limit: usize) -> Self {
+        self.spin_limit = Some(spin_limit);
+        self
+    }
+
+    /// Wait out a blocked hazard with `parker`.
+    pub fn parker(mut self, parker: impl Parker + Send + Sync + 'static) -> Self {
+        self.parker = Some(Box::new(parker));
+        self
+    }
+
+    /// Use sequentially consistent ordering, like [`create_seqcst`].
+    pub fn seqcst(mut self, seqcst: bool) -> Self {
+        self.seqcst = seqcst;
+        self
+    }
+
+    /// Instantiate the configured hazard pair.
+    pub fn build(self) -> (Reader, Writer) {
+        let (mut reader, writer) = create_pair(self.seqcst);
+
+        reader.policy = match (self.spin_limit, self.parker) {
+            (None, None) => None,
+            (spin_limit, parker) => Some(Box::new(Policy {
+                spin_limit,
+                parker: parker.unwrap_or_else(|| Box::new(ThreadParker::default())),
+            })),
+        };
+
+        (reader, writer)
+    }
+}
+
+impl fmt::Debug for HazardBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HazardBuilder")
+            .field("spin_limit", &self.spin_limit)
+            .field("parker", &self.parker.is_some())
+            .field("seqcst", &self.seqcst)
+            .finish()
+    }
+}
+
+/// the wait policy a [`HazardBuilder`] stores in its reader
+struct Policy {
+    spin_limit: Option<usize>,
+    parker: Box<dyn Parker + Send + Sync>,
+}
+
+impl Parker for Policy {
+    fn should_park(&self, spins: usize) -> bool {
+        match self.spin_limit {
+            Some(limit) => spins >= limit,
+            None => self.parker.should_park(spins),
+        }
+    }
+
+    fn park(&self) {
+        self.parker.park();
+    }
+}
+
+impl fmt::Debug for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Policy")
+            .field("spin_limit", &self.spin_limit)
+            .finish_non_exhaustive()
+    }
+}
+
+/// ordering used for loads of the hazard state
+fn load_ordering(seqcst: bool) -> Ordering {
+    if seqcst {
+        Ordering::SeqCst
+    } else {
+        Ordering::Acquire
+    }
+}
+
+/// ordering used for stores of the hazard state
+fn store_ordering(seqcst: bool) -> Ordering {
+    if seqcst {
+        Ordering::SeqCst
+    } else {
+        Ordering::Release
+    }
+}
+
+/// ordering used for read-modify-write operations on the hazard state
+fn rmw_ordering(seqcst: bool) -> Ordering {
+    if seqcst {
+        Ordering::SeqCst
+    } else {
+        Ordering::AcqRel
+    }
+}
+
+/// Run `f` with a fresh hazard reader-writer pair and tear the pair down afterwards.
+///
+/// The writer is killed and the reader destroyed once `f` returns, and also if `f` panics,
+/// so the pair is always reclaimed without any unsafe code on the caller's side.
+pub fn with_pair<R>(f: impl FnOnce(&Reader, &Writer) -> R) -> R {
+    struct Teardown {
+        reader: Option<Reader>,
+        writer: Option<Writer>,
+    }
+
+    impl Drop for Teardown {
+        fn drop(&mut self) {
+            if let Some(writer) = self.writer.take() {
+                writer.kill();
+            }
+            if let Some(reader) = self.reader.take() {
+                // the writer was killed just above, so the hazard is dead
+                unsafe { reader.destroy() };
+            }
+        }
+    }
+
+    let (reader, writer) = create();
+    let pair = Teardown {
+        reader: Some(reader),
+        writer: Some(writer),
+    };
+
+    f(pair.reader.as_ref().unwrap(), pair.writer.as_ref().unwrap())
+}
+
+/// Tear down a hazard pair created by [`create`], reclaiming its shared allocation.
+///
+/// `create` leaks a `Box<Slot>` so both halves can hold a `'static` reference to it.
+/// This kills the writer, checks that the reader observes `Dead`, and then frees that box.
+/// Taking both halves enforces the kill-then-destroy order.
+///
+/// # Safety
+///
+/// `reader` and `writer` must come from the same call to [`create`] or [`create_seqcst`].
+/// Debug builds check this and panic on a mismatch.
+pub unsafe fn reclaim_pair(reader: Reader, writer: Writer) {
+    debug_assert!(
+        reader.ptr == writer.ptr,
+        "reader and writer belong to different hazard pairs"
+    );
+
+    writer.kill();
+    reader.destroy();
+}
+
+/// A hazard pair whose writer runs on a spawned thread.
+///
+/// The writer is killed when the spawned closure returns, or dropped (and thereby marked dead)
+/// if it panics, so by the time [`JoinableHazard::join`] has joined the thread the hazard is
+/// guaranteed to be dead and the reader can be destroyed safely.
+///
+/// Always finish with [`JoinableHazard::join`]. Dropping the hazard instead drops its reader,
+/// which panics like any undestroyed [`Reader`], and leaves the spawned thread running
+/// detached.
+#[derive(Debug)]
+#[must_use = "a JoinableHazard must be joined; dropping it panics and detaches the thread"]
+pub struct JoinableHazard<R> {
+    reader: Reader,
+    handle: thread::JoinHandle<R>,
+}
+
+impl<R: Send + 'static> JoinableHazard<R> {
+    /// Create a hazard pair and run `f` with its writer on a new thread.
+    pub fn spawn<F>(f: F) -> Self
+    where
+        F: FnOnce(&Writer) -> R + Send + 'static,
+    {
+        let (reader, writer) = create();
+
+        let handle = thread::spawn(move || {
+            // if `f` panics, the writer's Drop marks the hazard dead while unwinding
+            let result = f(&writer);
+            writer.kill();
+            result
+        });
+
+        Self { reader, handle }
+    }
+
+    /// The reader observing the spawned writer.
+    pub fn reader(&self) -> &Reader {
+        &self.reader
+    }
+
+    /// Wait for the spawned thread, then destroy the hazard pair.
+    ///
+    /// Returns the closure's result, or the panic payload if the spawned thread panicked.
+    pub fn join(self) -> thread::Result<R> {
+        let result = self.handle.join();
+
+        debug_assert_eq!(self.reader.get(), State::Dead);
+        // the thread has finished, and either killed the writer or dropped it while panicking
+        unsafe { self.reader.destroy() };
+
+        result
+    }
+}
+
+/// A hazard writer that can be held across `.await` points.
+///
+/// A plain [`Writer`] panics when dropped, which makes it unusable in an `async fn`: a future
+/// is cancelled by dropping it, at whichever `.await` it is suspended. The shield kills its
+/// writer on drop instead, so cancellation releases the slot cleanly. The matching reader then
+/// observes `Dead` and can be reclaimed with [`Reader::destroy_if_dead`].
+///
+/// The shield is `Send`, so the future holding it can move between executor threads.
+/// It derefs to the [`Writer`] for `protect`, `free` and friends.
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub struct AsyncShield {
+    writer: Option<Writer>,
+}
+
+#[cfg(feature = "async")]
+impl AsyncShield {
+    /// Instantiate a new hazard pair whose writer is held by a shield.
+    pub fn create() -> (Reader, AsyncShield) {
+        let (reader, writer) = create();
+        (reader, AsyncShield::from(writer))
+    }
+}
+
+#[cfg(feature = "async")]
+impl From<Writer> for AsyncShield {
+    fn from(writer: Writer) -> Self {
+        Self {
+            writer: Some(writer),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl std::ops::Deref for AsyncShield {
+    type Target = Writer;
+
+    fn deref(&self) -> &Writer {
+        self.writer.as_ref().unwrap()
+    }
+}
+
+#[cfg(feature = "async")]
+impl Drop for AsyncShield {
+    fn drop(&mut self) {
+        if let Some(writer) = self.writer.take() {
+            writer.kill();
+        }
+    }
+}
+
+/// A wait policy for readers that find the hazard blocked, used by [`Reader::get_with_parker`].
+///
+/// The reader spins until [`Parker::should_park`] says otherwise, and from then on calls
+/// [`Parker::park`] between polls. Implementations can block on a futex or condvar, or hand
+/// control back to an async executor, without the crate committing to any of them.
+pub trait Parker {
+    /// whether to park rather than spin, after the reader found the hazard blocked `spins` times
+    fn should_park(&self, spins: usize) -> bool;
+
+    /// wait a while before the reader polls the hazard again
+    fn park(&self);
+}
+
+/// A [`Parker`] that never parks, so the reader busy-waits like [`Reader::get`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpinParker;
+
+impl Parker for SpinParker {
+    fn should_park(&self, _spins: usize) -> bool {
+        false
+    }
+
+    fn park(&self) {}
+}
+
+/// A [`Parker`] that spins `spin_limit` times and then yields the thread between polls.
+#[derive(Debug, Clone, Copy)]
+pub struct ThreadParker {
+    pub spin_limit: usize,
+}
+
+impl Default for ThreadParker {
+    fn default() -> Self {
+        Self { spin_limit: 64 }
+    }
+}
+
+impl Parker for ThreadParker {
+    fn should_park(&self, spins: usize) -> bool {
+        spins >= self.spin_limit
+    }
+
+    fn park(&self) {
+        thread::yield_now();
+    }
+}
+
 #[derive(Debug)]
 pub struct Reader {
-    ptr: &'static AtomicPtr<u8>,
+    /// the slot shared with the writer, freed by `Reader::destroy`
+    ptr: NonNull<Slot>,
+    seqcst: bool,
+    /// how `get` waits out a blocked hazard, or `None` to busy-wait
+    policy: Option<Box<Policy>>,
+    /// number of times this reader found the hazard blocked
+    spins: AtomicUsize,
+    /// whether this reader ever found the hazard unblocked
+    ever_unblocked: AtomicBool,
 }
 
+/// What a reader has observed of its hazard, for debugging readers stuck on a blocked slot.
+///
+/// Returned by [`Reader::diagnose`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockedDiagnosis {
+    /// the raw encoded state word at the time of the diagnosis
+    pub raw: *const u8,
+    /// whether the hazard was blocked at the time of the diagnosis
+    pub blocked: bool,
+    /// how many times this reader has found the hazard blocked
+    pub spins: usize,
+    /// whether this reader has ever found the hazard in a state other than blocked
+    ///
+    /// `false` after many spins suggests the writer never set the hazard up
+    /// (e.g. a forgotten `free`), rather than a writer that is merely slow.
+    pub ever_unblocked: bool,
+}
+
+// the slot is only accessed through atomics, and freed once both halves are done with it
+unsafe impl Send for Reader {}
+unsafe impl Sync for Reader {}
+
 impl Reader {
+    /// the slot shared with the writer
+    fn slot(&self) -> &Slot {
+        // only `destroy` frees the slot, and it consumes the reader
+        unsafe { self.ptr.as_ref() }
+    }
+
+    /// load the raw state word
+    fn raw(&self) -> *const u8 {
+        self.slot().state.load(load_ordering(self.seqcst)) as *const u8
+    }
+
+    /// load the raw state word, recording what was seen for [`Reader::diagnose`]
+    ///
+    /// Only a blocked hazard, where the reader is about to wait anyway, and the first
+    /// unblocked one are written down, so the common unblocked read stays a plain load.
+    fn load(&self) -> *const u8 {
+        let ptr = self.raw();
+
+        if ptr::eq(ptr, &BLOCKED) {
+            self.spins.fetch_add(1, Ordering::Relaxed);
+        } else if !self.ever_unblocked.load(Ordering::Relaxed) {
+            self.ever_unblocked.store(true, Ordering::Relaxed);
+        }
+
+        ptr
+    }
+
+    /// report what this reader has observed so far, without waiting for the hazard
+    ///
+    /// Looking at the hazard here is not counted as an observation.
+    pub fn diagnose(&self) -> BlockedDiagnosis {
+        let raw = self.raw();
+
+        BlockedDiagnosis {
+            raw,
+            blocked: ptr::eq(raw, &BLOCKED),
+            spins: self.spins.load(Ordering::Relaxed),
+            ever_unblocked: self.ever_unblocked.load(Ordering::Relaxed),
+        }
+    }
+
+    /// load the raw encoded state word once, without interpreting or waiting on it
+    ///
+    /// Protected pointers appear as themselves, and the other states as the addresses of the
+    /// crate's sentinels, such as [`free_sentinel`]. This is meant for protocols layered on top
+    /// of the slot; everything else should use [`Reader::get`].
+    pub fn observe_raw(&self) -> *const u8 {
+        self.raw()
+    }
+
+    /// get the state, waiting while the hazard is blocked
+    ///
+    /// A reader built with a wait policy (see [`builder`]) follows it. Otherwise this busy-waits,
+    /// which never hands control to the OS and suits realtime threads, or with the
+    /// `reader-blocking` feature spins briefly and then yields the thread like [`ThreadParker`],
+    /// which saves CPU when writers stay blocked for long.
     pub fn get(&self) -> State {
-        // counts the number of spins
-        let mut _spins = 0;
+        if let Some(policy) = &self.policy {
+            return self.get_with_parker(&**policy);
+        }
+
+        #[cfg(feature = "reader-blocking")]
+        let parker = ThreadParker::default();
+        #[cfg(not(feature = "reader-blocking"))]
+        let parker = SpinParker;
+
+        self.get_with_parker(&parker)
+    }
+
+    /// get the state, giving up once `timeout` has elapsed while the hazard is still blocked
+    ///
+    /// Spins briefly before yielding the thread between polls, and returns `Err(())` on timeout.
+    #[allow(clippy::result_unit_err)]
+    pub fn get_timeout(&self, timeout: Duration) -> Result<State, ()> {
+        let deadline = Instant::now() + timeout;
+        self.wait(&ThreadParker::default(), || Instant::now() >= deadline)
+            .ok_or(())
+    }
+
+    /// get the state, giving up once `cancel` is set while the hazard is still blocked
+    ///
+    /// Polls like [`Reader::get_timeout`] and returns `None` on cancellation, so a shutdown
+    /// path can release readers stuck on a writer that never unblocks.
+    pub fn get_cancellable(&self, cancel: &AtomicBool) -> Option<State> {
+        self.wait(&ThreadParker::default(), || cancel.load(Ordering::Acquire))
+    }
+
+    /// get the state, waiting out a blocked hazard according to `parker`
+    pub fn get_with_parker(&self, parker: &impl Parker) -> State {
+        match self.wait(parker, || false) {
+            Some(state) => state,
+            None => unreachable!("a wait that never gives up always resolves"),
+        }
+    }
+
+    /// poll the state until it is not blocked, waiting between polls according to `parker`
+    ///
+    /// Returns `None` if `give_up` returns `true`, which is checked each time the hazard is
+    /// found blocked.
+    fn wait(&self, parker: &impl Parker, mut give_up: impl FnMut() -> bool) -> Option<State> {
+        let mut spins = 0;
 
-        // spin until not blocked
         loop {
-            let ptr = self.ptr.load(Ordering::Acquire) as *const u8;
-
-            if ptr == &BLOCKED as *const u8 {
-                _spins += 1;
-                continue;
-            } else if ptr == &FREE as *const u8 {
-                return State::Free;
-            } else if ptr == &DEAD as *const u8 {
-                return State::Dead;
+            let ptr = self.load();
+
+            if !ptr::eq(ptr, &BLOCKED) {
+                let state = decode(ptr);
+                trace!("hazard {:p}: get -> {:?}", self.ptr, state);
+                return Some(state);
+            }
+
+            if give_up() {
+                return None;
+            }
+
+            if parker.should_park(spins) {
+                parker.park();
             } else {
-                return State::Protect(ptr);
+                hint::spin_loop();
+            }
+            spins += 1;
+        }
+    }
+
+    /// get the state without waiting on a writer that announced its next state
+    ///
+    /// When the hazard is blocked via [`Writer::block_announcing`], the reader completes the
+    /// writer's pending transition itself instead of spinning, so it finishes in a bounded
+    /// number of steps. A plain [`Writer::block`] still makes this spin like [`Reader::get`].
+    pub fn get_cooperative(&self) -> State {
+        loop {
+            let ptr = self.load();
+
+            if !ptr::eq(ptr, &BLOCKED) {
+                let state = decode(ptr);
+                trace!("hazard {:p}: get -> {:?}", self.ptr, state);
+                return state;
             }
+
+            // claim the announcement, so exactly one party installs it
+            let next = self
+                .slot()
+                .next
+                .swap(&BLOCKED as *const u8 as *mut u8, rmw_ordering(self.seqcst))
+                as *const u8;
+
+            if !ptr::eq(next, &BLOCKED) {
+                self.slot()
+                    .state
+                    .store(next as *mut u8, store_ordering(self.seqcst));
+                let state = decode(next);
+                trace!("hazard {:p}: get (helped) -> {:?}", self.ptr, state);
+                return state;
+            }
+
+            hint::spin_loop();
+        }
+    }
+
+    /// resolve the state and pass the protected object, if any, to `f` as a typed reference
+    ///
+    /// `f` receives `None` when the hazard is free, dead or protects a null pointer.
+    ///
+    /// # Safety
+    ///
+    /// If the hazard protects a non-null pointer, it must point to a valid `T`, and that
+    /// object must stay alive until `f` returns, which holds as long as the writer keeps
+    /// protecting it and the owner of the object honours the protection.
+    pub unsafe fn with_protected<T, R>(&self, f: impl FnOnce(Option<&T>) -> R) -> R {
+        match self.get() {
+            State::Protect(ptr) => f((ptr as *const T).as_ref()),
+            _ => f(None),
+        }
+    }
+
+    /// observe another hazard slot, consuming its reader
+    ///
+    /// Returns the reader for the slot previously observed, so the caller can tear it
+    /// down (e.g. with [`Reader::destroy`] once its writer is dead) rather than have it
+    /// dropped, which would panic.
+    #[must_use = "the previous reader must be destroyed"]
+    pub fn rebind(&mut self, other: Reader) -> Reader {
+        mem::replace(self, other)
+    }
+
+    /// destroy the hazard pointer if its writer is already dead, otherwise hand the reader back
+    ///
+    /// This never waits and never panics, so cleanup code can opportunistically reclaim readers
+    /// whose writers were killed. A dead hazard means the writer was killed or dropped, so no
+    /// writer can still be using the allocation.
+    pub fn destroy_if_dead(self) -> Result<(), Reader> {
+        if !ptr::eq(self.raw(), &DEAD) {
+            return Err(self);
         }
+
+        unsafe { self.destroy() };
+        Ok(())
     }
 
     /// destroy the hazard pointer
-    /// 
+    ///
     /// # Safety
-    /// 
-    /// This operation is considered unsafe because it assumes that 
-    /// the writer component is no longer active or in use. 
-    /// 
-    /// Since the type system cannot currently enforce this condition, 
+    ///
+    /// This operation is considered unsafe because it assumes that
+    /// the writer component is no longer active or in use.
+    ///
+    /// Since the type system cannot currently enforce this condition,
     /// it's crucial that the caller ensures this is the case
+    ///
+    /// # Panics
+    ///
+    /// Panics if the hazard is not dead, waiting first while it is blocked like
+    /// [`Reader::get`]. Debug builds also track whether the writer is still alive, and
+    /// panic right away with a message naming the correct teardown order if it is.
     pub unsafe fn destroy(self) {
+        #[cfg(debug_assertions)]
+        if self.slot().writer_alive.load(Ordering::Acquire) {
+            panic!("destroy called before writer killed — correct order is kill() then destroy()");
+        }
+
         if self.get() != State::Dead {
             panic!("hazard pointer is not dead");
         }
 
-        // load a pointer and deallocate it
-        drop(Box::from_raw(
-            self.ptr as *const AtomicPtr<u8> as *mut AtomicPtr<u8>,
-        ));
-        // ensure the pointer is not used again
-        mem::forget(self);
+        // disarm `Drop` before the slot is freed, so no `Reader` holding a dangling reference
+        // is ever moved or dropped
+        let mut this = ManuallyDrop::new(self);
+        drop(this.policy.take());
+        drop(Box::from_raw(this.ptr.as_ptr()));
     }
 }
 
 impl Drop for Reader {
     fn drop(&mut self) {
-        panic!("hazard pointer is not dead");
+        // panicking again while unwinding would abort, so leak the allocation instead
+        if !thread::panicking() {
+            panic!("hazard pointer is not dead");
+        }
     }
 }
 
 #[derive(Debug)]
 pub struct Writer {
-    ptr: &'static AtomicPtr<u8>,
+    /// the slot shared with the reader
+    ptr: NonNull<Slot>,
+    seqcst: bool,
 }
 
+unsafe impl Send for Writer {}
+unsafe impl Sync for Writer {}
+
 impl Writer {
+    /// the slot shared with the reader
+    fn slot(&self) -> &Slot {
+        // the reader may only free the slot once the writer is dead, which consumes the writer
+        unsafe { self.ptr.as_ref() }
+    }
+
     pub fn is_blocked(&self) -> bool {
-        self.ptr.load(Ordering::Acquire) == &BLOCKED as *const u8 as *mut u8
+        ptr::eq(self.slot().state.load(load_ordering(self.seqcst)), &BLOCKED)
+    }
+
+    /// check that no transition announced by `block_announcing` is still pending
+    ///
+    /// A reader may install an announcement at any time until the writer withdraws it in
+    /// `complete_announced`, so any other transition made before then could be overwritten.
+    fn debug_assert_no_announcement(&self) {
+        debug_assert!(
+            thread::panicking() || ptr::eq(self.slot().next.load(Ordering::Relaxed), &BLOCKED),
+            "hazard changed state with an announced transition pending; \
+             call complete_announced first"
+        );
+    }
+
+    /// store a new encoded state, tracing the transition
+    fn set(&self, _op: &str, new: *const u8) {
+        self.debug_assert_no_announcement();
+
+        // readers only store while an announced transition is pending, and the writer waits
+        // for that store before moving on, so the previous word cannot change under us
+        #[cfg(feature = "trace")]
+        let old = self.slot().state.load(Ordering::Relaxed);
+
+        self.slot()
+            .state
+            .store(new as *mut u8, store_ordering(self.seqcst));
+        trace!(
+            "hazard {:p}: {} {:?} -> {:?}",
+            self.ptr,
+            _op,
+            Word(old),
+            Word(new)
+        );
     }
 
     /// block the hazard pointer
     pub fn block(&self) {
-        self.ptr
-            .store(&BLOCKED as *const u8 as *mut u8, Ordering::Release);
+        self.set("block", &BLOCKED);
+    }
+
+    /// block the hazard pointer, announcing the state it will move to once unblocked
+    ///
+    /// Readers using [`Reader::get_cooperative`] complete the transition to `next` on the
+    /// writer's behalf instead of waiting for it. The writer must call
+    /// [`Writer::complete_announced`] before changing the state in any other way; debug builds
+    /// panic if it does not.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `next` is `State::Dead`; use [`Writer::kill`] to kill the hazard.
+    /// Also panics if `next` is `State::Blocked`, which is not a transition, or protects a
+    /// sentinel address, as [`Writer::protect`] does.
+    pub fn block_announcing(&self, next: State) {
+        // check the encoded word, which is what a reader installs
+        let next = encode(next);
+        assert!(
+            !ptr::eq(next, &DEAD),
+            "cannot announce a transition to dead"
+        );
+        assert!(
+            !ptr::eq(next, &BLOCKED),
+            "cannot announce a transition to blocked"
+        );
+
+        // block first, so a reader that finds the announcement always finds the hazard blocked
+        self.block();
+        self.slot()
+            .next
+            .store(next as *mut u8, store_ordering(self.seqcst));
+    }
+
+    /// finish the transition announced by [`Writer::block_announcing`]
+    ///
+    /// If a reader already claimed the announcement, this waits for the reader to install it,
+    /// which takes a bounded number of its steps.
+    pub fn complete_announced(&self) {
+        let next = self
+            .slot()
+            .next
+            .swap(&BLOCKED as *const u8 as *mut u8, rmw_ordering(self.seqcst))
+            as *const u8;
+
+        if !ptr::eq(next, &BLOCKED) {
+            self.set("unblock", next);
+            return;
+        }
+
+        while self.is_blocked() {
+            hint::spin_loop();
+        }
     }
 
     /// set the hazard pointer state to free
     pub fn free(&self) {
-        self.ptr
-            .store(&FREE as *const u8 as *mut u8, Ordering::Release);
+        self.set("free", &FREE);
     }
 
     /// protect a pointer
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ptr` is one of the sentinel addresses, such as [`dead_sentinel`], which
+    /// encode the other states.
     pub fn protect(&self, ptr: *const u8) {
-        self.ptr.store(ptr as *mut u8, Ordering::Release);
+        self.set("protect", protect_word(ptr));
+    }
+
+    /// protect a pointer, unless the hazard is already dead
+    ///
+    /// Returns `Err(())`, leaving the hazard dead, if it was killed; a plain
+    /// [`Writer::protect`] would overwrite the dead state and corrupt the protocol.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ptr` is a sentinel address, like [`Writer::protect`].
+    #[allow(clippy::result_unit_err)]
+    pub fn try_protect(&self, ptr: *const u8) -> Result<(), ()> {
+        let ptr = protect_word(ptr);
+        self.debug_assert_no_announcement();
+        let mut current = self.slot().state.load(load_ordering(self.seqcst));
+
+        loop {
+            if ptr::eq(current, &DEAD) {
+                return Err(());
+            }
+
+            match self.slot().state.compare_exchange_weak(
+                current,
+                ptr as *mut u8,
+                store_ordering(self.seqcst),
+                load_ordering(self.seqcst),
+            ) {
+                Ok(_) => {
+                    trace!(
+                        "hazard {:p}: protect {:?} -> {:?}",
+                        self.ptr,
+                        Word(current),
+                        Word(ptr)
+                    );
+                    return Ok(());
+                }
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// protect `ptr` while running `f`, and free the hazard afterwards even if `f` panics
+    ///
+    /// A panic is caught and returned as the `Err` payload, so a panicking callback can never
+    /// leave a protection behind to stall reclamation. `f` is treated as unwind safe; as with
+    /// [`std::panic::AssertUnwindSafe`], state it mutates may be left half-updated on panic.
+    pub fn protect_catch<R>(&self, ptr: *const u8, f: impl FnOnce() -> R) -> thread::Result<R> {
+        self.protect(ptr);
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(f));
+        self.free();
+        result
+    }
+
+    /// protect a non-null pointer
+    ///
+    /// Prefer this over [`Writer::protect`], which also accepts null and so can end up
+    /// protecting nothing by mistake.
+    pub fn protect_nonnull(&self, ptr: NonNull<u8>) {
+        self.protect(ptr.as_ptr());
+    }
+
+    /// protect a non-null pointer to a `T`
+    pub fn protect_nonnull_typed<T>(&self, ptr: NonNull<T>) {
+        self.protect_nonnull(ptr.cast());
+    }
+
+    /// protect the allocation behind a possibly unsized pointer, such as a slice or trait object
+    ///
+    /// Only the data address is published; metadata like a slice length or vtable is dropped,
+    /// and the caller keeps it separately. Hazards only guard the liveness of the allocation,
+    /// so the data address is all a reclaimer needs to compare against.
+    pub fn protect_fat<T: ?Sized>(&self, ptr: *const T) {
+        self.protect(ptr.cast::<u8>());
+    }
+
+    /// move to `new` and return the state it replaced, in a single atomic step
+    ///
+    /// This is a general read-modify-write over the state machine, for protocols built on the
+    /// slot such as handing it off only if it was free. Unlike [`Reader::get`], the returned
+    /// state may be `State::Blocked`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new` is `State::Dead`, or protects a sentinel address as [`Writer::protect`]
+    /// does. A dead hazard may be reclaimed by the reader at any moment, so the writer must not
+    /// outlive the transition; use [`Writer::kill`] instead.
+    pub fn swap_state(&self, new: State) -> State {
+        // check the encoded word, so no other state can smuggle in the dead sentinel
+        let new = encode(new);
+        assert!(
+            !ptr::eq(new, &DEAD),
+            "cannot swap to dead, use Writer::kill"
+        );
+        self.debug_assert_no_announcement();
+
+        let old = self
+            .slot()
+            .state
+            .swap(new as *mut u8, rmw_ordering(self.seqcst)) as *const u8;
+        trace!(
+            "hazard {:p}: swap {:?} -> {:?}",
+            self.ptr,
+            Word(old),
+            Word(new)
+        );
+
+        decode(old)
+    }
+
+    /// protect a pointer produced by `load`, retrying until it is stable
+    ///
+    /// `load` is called, its result protected, and `load` called again until both calls agree,
+    /// so the protection was published while the pointer was still current. This is the acquire
+    /// protocol of [`Atomic::acquire`](crate::atomic::Atomic::acquire) for any load source.
+    ///
+    /// Returns the protected pointer. If `load` returns null the hazard is set to free instead.
+    pub fn protect_from<T>(&self, load: impl Fn() -> *mut T) -> *mut T {
+        let mut current = load();
+
+        loop {
+            if current.is_null() {
+                self.free();
+                return current;
+            }
+
+            self.protect(current as *const u8);
+            // order the protection before the validating load
+            atomic::fence(Ordering::SeqCst);
+
+            let reloaded = load();
+            if reloaded == current {
+                // the address may have been freed and reused since the first load, so hand back
+                // the pointer from the validating load, which refers to the object installed now
+                return reloaded;
+            }
+            current = reloaded;
+        }
+    }
+
+    /// protect the current pointee of `src`, returning a guard that can re-protect it later
+    ///
+    /// See [`Pin::refresh`](crate::guard::Pin::refresh).
+    ///
+    /// # Safety
+    ///
+    /// The same as for [`Atomic::acquire`], for as long as the pin lives: an object unlinked
+    /// from `src` must not be freed while the pin may still protect it, i.e. before the pin is
+    /// refreshed past it or dropped.
+    pub unsafe fn pin_to<'a, T>(&'a self, src: &'a Atomic<T>) -> Pin<'a, T> {
+        Pin::new(src, self)
+    }
+
+    /// set the hazard pointer state to free, but only if it currently protects a pointer
+    ///
+    /// Returns whether the state was changed. Blocked, free and dead hazards are left untouched.
+    pub fn free_if_protecting(&self) -> bool {
+        self.debug_assert_no_announcement();
+        let mut current = self.slot().state.load(load_ordering(self.seqcst));
+
+        loop {
+            if is_sentinel(current) {
+                return false;
+            }
+
+            match self.slot().state.compare_exchange_weak(
+                current,
+                &FREE as *const u8 as *mut u8,
+                store_ordering(self.seqcst),
+                load_ordering(self.seqcst),
+            ) {
+                Ok(_) => {
+                    trace!(
+                        "hazard {:p}: free {:?} -> {:?}",
+                        self.ptr,
+                        Word(current),
+                        Word(&FREE)
+                    );
+                    return true;
+                }
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// withdraw the current protection, telling the reader to re-acquire
+    ///
+    /// If the hazard protects a pointer, it moves to `State::Invalidated`, which readers can
+    /// tell apart from a plain `Free`: the object is being reclaimed and any pointer obtained
+    /// under the old protection must be discarded. This makes the protection a hint that a
+    /// cooperative reclaimer can revoke, rather than a hard guarantee.
+    ///
+    /// Returns whether a protection was invalidated. Blocked, free and dead hazards are left
+    /// untouched.
+    pub fn invalidate_protection(&self) -> bool {
+        self.debug_assert_no_announcement();
+        let mut current = self.slot().state.load(load_ordering(self.seqcst));
+
+        loop {
+            if is_sentinel(current) {
+                return false;
+            }
+
+            match self.slot().state.compare_exchange_weak(
+                current,
+                &INVALIDATED as *const u8 as *mut u8,
+                store_ordering(self.seqcst),
+                load_ordering(self.seqcst),
+            ) {
+                Ok(_) => {
+                    trace!(
+                        "hazard {:p}: invalidate {:?} -> {:?}",
+                        self.ptr,
+                        Word(current),
+                        Word(&INVALIDATED)
+                    );
+                    return true;
+                }
+                Err(actual) => current = actual,
+            }
+        }
     }
 
     /// set the hazard pointer state to dead
-    /// 
+    ///
     /// # Safety
-    /// 
-    /// This approach is unsafe because using the system after this call breaks invariants. 
+    ///
+    /// This approach is unsafe because using the system after this call breaks invariants.
     /// To maintain safety within the type system, use `Writer::kill()`.
     unsafe fn dead(&self) {
-        self.ptr
-            .store(&DEAD as *const u8 as *mut u8, Ordering::Release);
+        // cleared first, so a reader that sees the hazard dead also sees the writer gone
+        #[cfg(debug_assertions)]
+        self.slot().writer_alive.store(false, Ordering::Release);
+        self.set("kill", &DEAD);
+    }
+
+    /// set the hazard pointer state to dead without consuming the writer
+    ///
+    /// Only available with the `testing` feature, for exercising use-after-kill paths.
+    ///
+    /// # Safety
+    ///
+    /// The writer must not protect anything afterwards, and must be forgotten with
+    /// [`mem::forget`] rather than dropped, since the reader may reclaim the hazard at any time.
+    #[cfg(feature = "testing")]
+    pub unsafe fn kill_in_place(&self) {
+        self.dead();
     }
 
     /// set the hazard pointer state to dead
@@ -1,4 +1,10 @@
-#[allow(non_snake_case)]
+#![allow(non_snake_case)]
 pub mod atomic;
+pub mod backoff;
+pub mod domain;
 pub mod guard;
 pub mod hazard;
+pub mod local;
+pub mod ring;
+#[cfg(feature = "testing")]
+pub mod testing;
@@ -0,0 +1,141 @@
+//! A single-threaded counterpart of [`Atomic<T>`](crate::atomic::Atomic).
+
+use std::{cell::Cell, marker::PhantomData, ptr, sync::atomic::Ordering};
+
+use crate::atomic::PointerCell;
+
+/// A pointer cell with the same ownership rules as [`Atomic<T>`](crate::atomic::Atomic), but
+/// without any synchronization.
+///
+/// It is backed by a `Cell` instead of an atomic, so it is neither `Send` nor `Sync` and the
+/// compiler rejects any attempt to share it between threads:
+///
+/// ```compile_fail
+/// use STM::local::LocalAtomic;
+///
+/// let cell = LocalAtomic::new(Some(Box::new(1)));
+/// std::thread::spawn(move || drop(cell));
+/// ```
+///
+/// The orderings taken by its methods are accepted for API compatibility and ignored.
+/// Algorithms written against [`PointerCell`] can use either type.
+#[derive(Debug)]
+pub struct LocalAtomic<T> {
+    inner: Cell<*mut T>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> LocalAtomic<T> {
+    pub fn new(init: Option<Box<T>>) -> Self {
+        Self {
+            inner: Cell::new(init.map_or(ptr::null_mut(), Box::into_raw)),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Create a cell holding the given box, which is always non-null.
+    pub fn from_box(init: Box<T>) -> Self {
+        Self::new(Some(init))
+    }
+
+    /// Load the current pointer.
+    pub fn load(&self, _order: Ordering) -> *mut T {
+        self.inner.get()
+    }
+
+    /// Get a mutable reference to the pointee.
+    ///
+    /// `None` corresponds to a null pointer.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.inner.get().as_mut() }
+    }
+
+    /// Swap in `new` and return the previous value along with the now-current pointer.
+    ///
+    /// See [`Atomic::exchange_reporting`](crate::atomic::Atomic::exchange_reporting).
+    pub fn exchange_reporting(
+        &self,
+        new: Option<Box<T>>,
+        _order: Ordering,
+    ) -> (Option<Box<T>>, *mut T) {
+        let new = new.map_or(ptr::null_mut(), Box::into_raw);
+        let old = self.inner.replace(new);
+
+        let old = if old.is_null() {
+            None
+        } else {
+            Some(unsafe { Box::from_raw(old) })
+        };
+
+        (old, new)
+    }
+
+    /// Install `new` if the cell still holds `current`.
+    ///
+    /// See [`Atomic::compare_exchange`](crate::atomic::Atomic::compare_exchange).
+    pub fn compare_exchange(
+        &self,
+        current: *mut T,
+        new: Option<Box<T>>,
+        _success: Ordering,
+        _failure: Ordering,
+    ) -> Result<*mut T, (Option<Box<T>>, *mut T)> {
+        let actual = self.inner.get();
+
+        if actual == current {
+            self.inner.set(new.map_or(ptr::null_mut(), Box::into_raw));
+            Ok(actual)
+        } else {
+            Err((new, actual))
+        }
+    }
+
+    /// Repeatedly replace the current pointer with one computed from it.
+    ///
+    /// See [`Atomic::cas_loop`](crate::atomic::Atomic::cas_loop). Without other threads the
+    /// first attempt always succeeds, unless `make_new` itself changes the cell.
+    pub fn cas_loop(&self, mut make_new: impl FnMut(*mut T) -> Option<Box<T>>) -> *mut T {
+        loop {
+            let current = self.inner.get();
+
+            let Some(new) = make_new(current) else {
+                return current;
+            };
+
+            // `make_new` may have changed the cell through a shared reference
+            if self.inner.get() == current {
+                let new = Box::into_raw(new);
+                self.inner.set(new);
+                return new;
+            }
+        }
+    }
+}
+
+impl<T> PointerCell<T> for LocalAtomic<T> {
+    fn new(init: Option<Box<T>>) -> Self {
+        LocalAtomic::new(init)
+    }
+
+    fn load(&self, order: Ordering) -> *mut T {
+        LocalAtomic::load(self, order)
+    }
+
+    fn exchange_reporting(&self, new: Option<Box<T>>, order: Ordering) -> (Option<Box<T>>, *mut T) {
+        LocalAtomic::exchange_reporting(self, new, order)
+    }
+
+    fn compare_exchange(
+        &self,
+        current: *mut T,
+        new: Option<Box<T>>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<*mut T, (Option<Box<T>>, *mut T)> {
+        LocalAtomic::compare_exchange(self, current, new, success, failure)
+    }
+
+    fn cas_loop(&self, make_new: impl FnMut(*mut T) -> Option<Box<T>>) -> *mut T {
+        LocalAtomic::cas_loop(self, make_new)
+    }
+}
@@ -1,3 +1,4 @@
+#![allow(non_snake_case)]
 fn main() {
     println!("Hello, world!");
 }
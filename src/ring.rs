@@ -0,0 +1,161 @@
+//! A bounded single-producer single-consumer ring buffer.
+
+use std::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// A bounded ring buffer shared by exactly one [`Producer`] and one [`Consumer`].
+///
+/// Both sides are wait-free: a push or pop is a couple of loads and one store, and never
+/// retries. Values live inline in the buffer, and every slot is owned by exactly one side at
+/// a time, so no reclamation is needed.
+///
+/// ```
+/// use STM::ring::SpscRing;
+///
+/// let (mut tx, mut rx) = SpscRing::new(2);
+/// tx.push(1).unwrap();
+/// tx.push(2).unwrap();
+/// assert_eq!(tx.push(3), Err(3));
+/// assert_eq!(rx.pop(), Some(1));
+/// ```
+pub struct SpscRing<T> {
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    /// index of the next slot to pop, only written by the consumer
+    head: AtomicUsize,
+    /// index of the next slot to push, only written by the producer
+    tail: AtomicUsize,
+    // Both indices run over `0..2 * capacity` and name slot `index % capacity`. Counting
+    // twice around tells a full ring (`capacity` apart) from an empty one (equal) without
+    // relying on integer overflow, which would skip slots when `capacity` does not divide
+    // `usize::MAX + 1`.
+}
+
+// a slot is only accessed by the side that owns it, as handed over through `head` and `tail`
+unsafe impl<T: Send> Send for SpscRing<T> {}
+unsafe impl<T: Send> Sync for SpscRing<T> {}
+
+impl<T> SpscRing<T> {
+    /// Create a ring holding up to `capacity` values and return its two ends.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero or greater than `usize::MAX / 2`.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(capacity: usize) -> (Producer<T>, Consumer<T>) {
+        assert!(capacity > 0, "ring capacity must be non-zero");
+        assert!(capacity <= usize::MAX / 2, "ring capacity is too large");
+
+        let ring = Arc::new(SpscRing {
+            slots: (0..capacity)
+                .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+                .collect(),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        });
+
+        (Producer { ring: ring.clone() }, Consumer { ring })
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// the slot an index in `0..2 * capacity` names
+    fn slot(&self, index: usize) -> *mut MaybeUninit<T> {
+        self.slots[index % self.slots.len()].get()
+    }
+
+    /// the index after `index`, wrapping at `2 * capacity`
+    fn next(&self, index: usize) -> usize {
+        if index + 1 == 2 * self.slots.len() {
+            0
+        } else {
+            index + 1
+        }
+    }
+
+    /// the number of values between `head` and `tail`
+    fn len(&self, head: usize, tail: usize) -> usize {
+        if tail >= head {
+            tail - head
+        } else {
+            tail + 2 * self.slots.len() - head
+        }
+    }
+}
+
+impl<T> Drop for SpscRing<T> {
+    fn drop(&mut self) {
+        let mut head = *self.head.get_mut();
+        let tail = *self.tail.get_mut();
+
+        while head != tail {
+            unsafe { (*self.slot(head)).assume_init_drop() };
+            head = self.next(head);
+        }
+    }
+}
+
+/// The pushing end of an [`SpscRing`].
+pub struct Producer<T> {
+    ring: Arc<SpscRing<T>>,
+}
+
+impl<T> Producer<T> {
+    /// Push `value`, or hand it back if the ring is full.
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        let tail = self.ring.tail.load(Ordering::Relaxed);
+        // acquire the consumer's moves out of the slots it released
+        let head = self.ring.head.load(Ordering::Acquire);
+
+        if self.ring.len(head, tail) == self.ring.capacity() {
+            return Err(value);
+        }
+
+        unsafe { (*self.ring.slot(tail)).write(value) };
+        // publish the value to the consumer
+        self.ring
+            .tail
+            .store(self.ring.next(tail), Ordering::Release);
+        Ok(())
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+}
+
+/// The popping end of an [`SpscRing`].
+pub struct Consumer<T> {
+    ring: Arc<SpscRing<T>>,
+}
+
+impl<T> Consumer<T> {
+    /// Pop the oldest value, or `None` if the ring is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let head = self.ring.head.load(Ordering::Relaxed);
+        // acquire the producer's writes into the slots it published
+        let tail = self.ring.tail.load(Ordering::Acquire);
+
+        if head == tail {
+            return None;
+        }
+
+        let value = unsafe { (*self.ring.slot(head)).assume_init_read() };
+        // hand the slot back to the producer
+        self.ring
+            .head
+            .store(self.ring.next(head), Ordering::Release);
+        Some(value)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+}
@@ -0,0 +1,103 @@
+//! Utilities for stress-testing concurrent structures built on this crate.
+//!
+//! Only available with the `testing` feature.
+
+use std::thread;
+
+/// Runs a closure concurrently from several threads, many times each.
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use STM::testing::StressHarness;
+///
+/// let counter = AtomicUsize::new(0);
+/// StressHarness::new(4, 100).run(|| {
+///     counter.fetch_add(1, Ordering::Relaxed);
+/// });
+/// assert_eq!(counter.load(Ordering::Relaxed), 400);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StressHarness {
+    threads: usize,
+    iterations: usize,
+    single_threaded: bool,
+}
+
+impl StressHarness {
+    /// Create a harness spawning `threads` threads, each running the closure `iterations` times.
+    ///
+    /// Under Miri the harness defaults to the single-threaded fallback.
+    pub fn new(threads: usize, iterations: usize) -> Self {
+        Self {
+            threads,
+            iterations,
+            single_threaded: cfg!(miri),
+        }
+    }
+
+    /// Run every iteration of every "thread" sequentially on the calling thread.
+    ///
+    /// The total number of calls stays `threads * iterations`, which keeps the
+    /// harness usable under Miri where spawning many threads is prohibitively slow.
+    pub fn single_threaded(mut self, single_threaded: bool) -> Self {
+        self.single_threaded = single_threaded;
+        self
+    }
+
+    /// Run `f` against the shared state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any invocation of `f` panicked, reporting how many threads failed.
+    pub fn run<F>(&self, f: F)
+    where
+        F: Fn() + Sync,
+    {
+        self.run_indexed(|_| f());
+    }
+
+    /// Run `f` against the shared state, passing the index of the running thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any invocation of `f` panicked, reporting how many threads failed.
+    pub fn run_indexed<F>(&self, f: F)
+    where
+        F: Fn(usize) + Sync,
+    {
+        if self.single_threaded {
+            for _ in 0..self.iterations {
+                for id in 0..self.threads {
+                    f(id);
+                }
+            }
+            return;
+        }
+
+        let f = &f;
+        let failed = thread::scope(|s| {
+            let handles: Vec<_> = (0..self.threads)
+                .map(|id| {
+                    s.spawn(move || {
+                        for _ in 0..self.iterations {
+                            f(id);
+                        }
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|h| h.join())
+                .filter(Result::is_err)
+                .count()
+        });
+
+        assert!(
+            failed == 0,
+            "{} of {} stress threads panicked",
+            failed,
+            self.threads
+        );
+    }
+}
@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod async_tests {
+    use std::{
+        future::Future,
+        pin::pin,
+        sync::mpsc,
+        task::{Context, Poll, Waker},
+        thread,
+    };
+
+    use STM::hazard::{AsyncShield, State};
+
+    /// a future that stays pending until polled a second time
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: std::pin::Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                Poll::Ready(())
+            } else {
+                self.0 = true;
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn cancellation_releases_slot() {
+        static X: u8 = 3;
+        let (r, shield) = AsyncShield::create();
+
+        let task = async move {
+            shield.protect(&X);
+            YieldOnce(false).await;
+            shield.free();
+        };
+
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut task = Box::pin(task);
+        assert!(task.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(r.get(), State::Protect(&X));
+
+        // cancel the task while it is suspended at the await
+        drop(task);
+
+        assert_eq!(r.get(), State::Dead);
+        assert!(r.destroy_if_dead().is_ok());
+    }
+
+    #[test]
+    fn completed_task_releases_slot() {
+        let (r, shield) = AsyncShield::create();
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut task = pin!(async move {
+            shield.free();
+            YieldOnce(false).await;
+        });
+        while task.as_mut().poll(&mut cx).is_pending() {}
+
+        assert!(r.destroy_if_dead().is_ok());
+    }
+
+    #[test]
+    fn shield_is_send() {
+        let (r, shield) = AsyncShield::create();
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            shield.free();
+            tx.send(shield).unwrap();
+        })
+        .join()
+        .unwrap();
+
+        drop(rx.recv().unwrap());
+        assert!(r.destroy_if_dead().is_ok());
+    }
+}
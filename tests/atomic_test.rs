@@ -0,0 +1,667 @@
+#[cfg(test)]
+mod atomic_tests {
+    use std::{
+        mem, ptr,
+        sync::atomic::{self, AtomicUsize, Ordering},
+        thread,
+    };
+
+    use STM::atomic::{Atomic, AtomicArray, AtomicValue, CasOutcome, PaddedAtomic};
+    use STM::hazard::{create, State};
+    #[cfg(feature = "testing")]
+    use STM::testing::StressHarness;
+
+    struct Node {
+        value: usize,
+        next: *mut Node,
+    }
+
+    // nodes are only linked through the atomic, which publishes them with release/acquire
+    unsafe impl Send for Node {}
+    unsafe impl Sync for Node {}
+
+    /// walk a chain starting at `head`, freeing every node and collecting the values
+    fn drain(head: *mut Node) -> Vec<usize> {
+        let mut values = Vec::new();
+        let mut cur = head;
+
+        while !cur.is_null() {
+            let node = unsafe { Box::from_raw(cur) };
+            values.push(node.value);
+            cur = node.next;
+        }
+
+        values
+    }
+
+    #[test]
+    #[cfg(feature = "testing")]
+    fn cas_loop_appends_under_contention() {
+        let chain: Atomic<Node> = Atomic::new(None);
+
+        StressHarness::new(8, 1_000).run_indexed(|id| {
+            let installed = chain.cas_loop(|head| {
+                Some(Box::new(Node {
+                    value: id,
+                    next: head,
+                }))
+            });
+            assert!(!installed.is_null());
+        });
+
+        let head = unsafe { chain.get_inner() }.swap(ptr::null_mut(), Ordering::Acquire);
+        let values = drain(head);
+
+        assert_eq!(values.len(), 8_000);
+        for id in 0..8 {
+            assert_eq!(values.iter().filter(|&&v| v == id).count(), 1_000);
+        }
+    }
+
+    #[test]
+    fn cas_loop_abort() {
+        let a = Atomic::new(Some(Box::new(Node {
+            value: 1,
+            next: ptr::null_mut(),
+        })));
+        let before = unsafe { a.get_inner() }.load(Ordering::Acquire);
+
+        let after = a.cas_loop(|_| None);
+        assert_eq!(before, after);
+
+        drain(unsafe { a.get_inner() }.swap(ptr::null_mut(), Ordering::Acquire));
+    }
+
+    #[test]
+    fn from_box() {
+        let a: Atomic<usize> = Box::new(42).into();
+        let p = unsafe { a.get_inner() }.load(Ordering::Acquire);
+        assert_eq!(unsafe { *p }, 42);
+
+        let b = Atomic::from_box(Box::new(7usize));
+        let p = unsafe { b.get_inner() }.swap(ptr::null_mut(), Ordering::Acquire);
+        assert_eq!(*unsafe { Box::from_raw(p) }, 7);
+
+        drop(unsafe { Box::from_raw(a.get_inner().load(Ordering::Acquire)) });
+    }
+
+    #[test]
+    fn get_mut() {
+        let mut a = Atomic::from_box(Box::new(1usize));
+        *a.get_mut().unwrap() += 41;
+        assert_eq!(a.get_mut().copied(), Some(42));
+
+        let mut empty: Atomic<usize> = Atomic::new(None);
+        assert!(empty.get_mut().is_none());
+
+        drop(unsafe { Box::from_raw(a.get_inner().load(Ordering::Acquire)) });
+    }
+
+    #[test]
+    fn acquire() {
+        let (r, w) = create();
+        let a = Atomic::from_box(Box::new(42usize));
+
+        {
+            let guard = unsafe { a.acquire(&w) }.unwrap();
+            assert_eq!(*guard, 42);
+            assert_eq!(r.get(), State::Protect(guard.as_ptr() as *const u8));
+        }
+        assert_eq!(r.get(), State::Free);
+
+        let empty: Atomic<usize> = Atomic::new(None);
+        assert!(unsafe { empty.acquire(&w) }.is_none());
+        assert_eq!(r.get(), State::Free);
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+        drop(unsafe { Box::from_raw(a.get_inner().load(Ordering::Acquire)) });
+    }
+
+    #[test]
+    fn acquire_while_swapping() {
+        let (r, w) = create();
+        let a = Atomic::from_box(Box::new(0usize));
+        w.free();
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                for i in 1..=1_000 {
+                    let old =
+                        unsafe { a.get_inner() }.swap(Box::into_raw(Box::new(i)), Ordering::AcqRel);
+                    atomic::fence(Ordering::SeqCst);
+                    // reclaim only once the reading thread no longer protects the old node
+                    while r.get() == State::Protect(old as *const u8) {
+                        std::hint::spin_loop();
+                    }
+                    drop(unsafe { Box::from_raw(old) });
+                }
+            });
+
+            for _ in 0..1_000 {
+                // the swapping thread frees a node only once the reader stops reporting it protected
+                let guard = unsafe { a.acquire(&w) }.unwrap();
+                assert!(*guard <= 1_000);
+            }
+        });
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+        drop(unsafe { Box::from_raw(a.get_inner().load(Ordering::Acquire)) });
+    }
+
+    #[test]
+    fn exchange_reporting() {
+        let a: Atomic<usize> = Atomic::new(None);
+
+        let first = Box::new(1);
+        let first_addr = &*first as *const usize as *mut usize;
+        let (old, current) = a.exchange_reporting(Some(first), Ordering::AcqRel);
+        assert!(old.is_none());
+        assert_eq!(current, first_addr);
+
+        let (old, current) = a.exchange_reporting(None, Ordering::AcqRel);
+        assert_eq!(old.as_deref(), Some(&1));
+        assert!(current.is_null());
+        assert!(unsafe { a.get_inner() }.load(Ordering::Acquire).is_null());
+    }
+
+    #[test]
+    fn padded_atomic() {
+        assert_eq!(mem::align_of::<PaddedAtomic<usize>>(), 128);
+
+        let slots: Vec<PaddedAtomic<usize>> = (0..4)
+            .map(|i| Atomic::from_box(Box::new(i)).into())
+            .collect();
+        for pair in slots.windows(2) {
+            let a = &pair[0] as *const _ as usize;
+            let b = &pair[1] as *const _ as usize;
+            assert!(b - a >= 128);
+        }
+
+        for (i, mut slot) in slots.into_iter().enumerate() {
+            assert_eq!(slot.get_mut().copied(), Some(i));
+            drop(unsafe { Box::from_raw(slot.get_inner().load(Ordering::Acquire)) });
+        }
+    }
+
+    #[test]
+    fn compare_exchange_outcome() {
+        let a: Atomic<usize> = Atomic::new(None);
+
+        match a.compare_exchange_outcome(
+            ptr::null_mut(),
+            Some(Box::new(1)),
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            CasOutcome::Installed { old } => assert!(old.is_null()),
+            CasOutcome::Failed { .. } => panic!("expected the swap to succeed"),
+        }
+
+        let installed = unsafe { a.get_inner() }.load(Ordering::Acquire);
+        match a.compare_exchange_outcome(
+            ptr::null_mut(),
+            Some(Box::new(2)),
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            CasOutcome::Installed { .. } => panic!("expected the swap to fail"),
+            CasOutcome::Failed { rejected, current } => {
+                assert_eq!(rejected.as_deref(), Some(&2));
+                assert_eq!(current, installed);
+            }
+        }
+
+        let old = a
+            .compare_exchange(installed, None, Ordering::AcqRel, Ordering::Acquire)
+            .unwrap();
+        assert_eq!(*unsafe { Box::from_raw(old) }, 1);
+    }
+
+    #[test]
+    fn guard() {
+        let (r, w) = create();
+        let a = Atomic::from_box(Box::new(String::from("hazard")));
+
+        let guard = unsafe { a.guard(&w) };
+        assert_eq!(guard.get().map(String::len), Some(6));
+        let addr = guard.get().unwrap() as *const String as *const u8;
+        assert_eq!(r.get(), State::Protect(addr));
+        assert_eq!(guard.get().map(String::as_str), Some("hazard"));
+        drop(guard);
+        assert_eq!(r.get(), State::Free);
+
+        let empty: Atomic<String> = Atomic::new(None);
+        let guard = unsafe { empty.guard(&w) };
+        assert!(guard.is_none());
+        assert!(guard.get().is_none());
+        drop(guard);
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+        drop(unsafe { Box::from_raw(a.get_inner().load(Ordering::Acquire)) });
+    }
+
+    #[test]
+    fn set_once_race() {
+        for _ in 0..100 {
+            let cell: Atomic<usize> = Atomic::new(None);
+
+            let results: Vec<_> = thread::scope(|s| {
+                let handles: Vec<_> = (0..2)
+                    .map(|i| {
+                        let cell = &cell;
+                        s.spawn(move || cell.set_once(Box::new(i)).map_err(|b| *b))
+                    })
+                    .collect();
+                handles.into_iter().map(|h| h.join().unwrap()).collect()
+            });
+
+            let winners: Vec<_> = (0..2).filter(|&i| results[i].is_ok()).collect();
+            assert_eq!(winners.len(), 1);
+
+            // the loser got its own value back, and the winner's value is installed
+            let loser = 1 - winners[0];
+            assert_eq!(results[loser], Err(loser));
+            let installed = unsafe { Box::from_raw(cell.load(Ordering::Acquire)) };
+            assert_eq!(*installed, winners[0]);
+        }
+    }
+
+    #[test]
+    fn get_or_init() {
+        let cell: Atomic<usize> = Atomic::new(None);
+        let calls = AtomicUsize::new(0);
+
+        let seen: Vec<usize> = thread::scope(|s| {
+            let handles: Vec<_> = (0..8)
+                .map(|i| {
+                    let (cell, calls) = (&cell, &calls);
+                    s.spawn(move || {
+                        cell.get_or_init(|| {
+                            calls.fetch_add(1, Ordering::Relaxed);
+                            Box::new(i)
+                        }) as usize
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        let installed = cell.load(Ordering::Acquire);
+        assert!(seen.iter().all(|&p| p == installed as usize));
+        assert!((1..=8).contains(&calls.load(Ordering::Relaxed)));
+
+        // once initialized, init is not called again
+        let again = cell.get_or_init(|| unreachable!());
+        assert_eq!(again, installed);
+
+        drop(unsafe { Box::from_raw(installed) });
+
+        // the winner's pointer is valid for writes, not just reads
+        let mut cell: Atomic<usize> = Atomic::new(None);
+        unsafe { *cell.get_or_init(|| Box::new(1)) = 2 };
+        assert_eq!(cell.get_mut(), Some(&mut 2));
+        drop(cell.try_into_box());
+    }
+
+    #[test]
+    fn snapshot2() {
+        const STEPS: usize = 10_000;
+        // the values are never freed while the test runs, so the snapshot can be dereferenced
+        let values: Vec<usize> = (0..=STEPS)
+            .map(|i| Box::into_raw(Box::new(i)) as usize)
+            .collect();
+        let a = Atomic::new(None);
+        let b = Atomic::new(None);
+        unsafe {
+            a.get_inner()
+                .store(values[0] as *mut usize, Ordering::Release);
+            b.get_inner()
+                .store(values[0] as *mut usize, Ordering::Release);
+        }
+
+        thread::scope(|s| {
+            let values = &values;
+            let (a, b) = (&a, &b);
+
+            s.spawn(move || {
+                // a always runs at most one step ahead of b
+                for &value in &values[1..] {
+                    unsafe {
+                        a.get_inner().store(value as *mut usize, Ordering::Release);
+                        b.get_inner().store(value as *mut usize, Ordering::Release);
+                    }
+                }
+            });
+
+            for _ in 0..STEPS {
+                let (pa, pb) = Atomic::snapshot2(a, b);
+                let (va, vb) = unsafe { (*pa, *pb) };
+                assert!(va == vb || va == vb + 1, "torn snapshot ({}, {})", va, vb);
+            }
+        });
+
+        for value in values {
+            drop(unsafe { Box::from_raw(value as *mut usize) });
+        }
+    }
+
+    #[test]
+    fn zero_sized() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Debug)]
+        struct Unit;
+
+        impl Drop for Unit {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let mut a: Atomic<Unit> = Atomic::new(None);
+        assert!(a.load(Ordering::Acquire).is_null());
+        assert!(a.get_mut().is_none());
+
+        assert!(a.set_once(Box::new(Unit)).is_ok());
+        let installed = a.load(Ordering::Acquire);
+        assert!(!installed.is_null());
+        assert!(a.get_mut().is_some());
+        drop(a.set_once(Box::new(Unit)).unwrap_err());
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+
+        let (r, w) = create();
+        assert!(unsafe { a.acquire(&w) }.is_some());
+        assert_eq!(r.get(), State::Free);
+
+        // boxes of a zero-sized type share one address, so a CAS cannot tell them apart
+        let old = a
+            .compare_exchange(
+                installed,
+                Some(Box::new(Unit)),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .unwrap();
+        assert_eq!(old, installed);
+        drop(unsafe { Box::from_raw(old) });
+        assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+
+        let (old, current) = a.exchange_reporting(None, Ordering::AcqRel);
+        assert!(old.is_some());
+        assert!(current.is_null());
+        drop(old);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 3);
+
+        let ptr = a.get_or_init(|| Box::new(Unit));
+        assert!(!ptr.is_null());
+        let (old, _) = a.exchange_reporting(None, Ordering::AcqRel);
+        drop(old);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 4);
+
+        let empty: Atomic<[u8; 0]> = Atomic::from_box(Box::new([]));
+        let (old, _) = empty.exchange_reporting(None, Ordering::AcqRel);
+        assert_eq!(old.as_deref(), Some(&[]));
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn atomic_array_indexed_cas() {
+        let array: AtomicArray<usize> = AtomicArray::new(4);
+        assert_eq!(array.len(), 4);
+        assert!((0..4).all(|i| array.load(i, Ordering::Acquire).is_null()));
+
+        let old = array
+            .compare_exchange(
+                2,
+                ptr::null_mut(),
+                Some(Box::new(7)),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .unwrap();
+        assert!(old.is_null());
+        let installed = array.load(2, Ordering::Acquire);
+        assert_eq!(unsafe { *installed }, 7);
+
+        // the other slots are untouched, and a stale CAS hands the box back
+        assert!(array.load(1, Ordering::Acquire).is_null());
+        let (rejected, actual) = array
+            .compare_exchange(
+                2,
+                ptr::null_mut(),
+                Some(Box::new(8)),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .unwrap_err();
+        assert_eq!(rejected.as_deref(), Some(&8));
+        assert_eq!(actual, installed);
+
+        let old = array.store(2, None, Ordering::AcqRel);
+        assert_eq!(old, installed);
+        drop(unsafe { Box::from_raw(old) });
+
+        assert_eq!(
+            unsafe { array.get_unchecked(3) }.load(Ordering::Acquire),
+            array.load(3, Ordering::Acquire)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn atomic_array_out_of_bounds() {
+        let array: AtomicArray<usize> = AtomicArray::new(2);
+        array.load(2, Ordering::Acquire);
+    }
+
+    #[test]
+    fn atomic_array_drop_frees_slots() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Counted;
+
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let array: AtomicArray<Counted> = AtomicArray::new(8);
+        for i in (0..8).step_by(2) {
+            assert!(array
+                .store(i, Some(Box::new(Counted)), Ordering::Release)
+                .is_null());
+        }
+
+        drop(array);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn pin_to_refresh() {
+        let src = Atomic::from_box(Box::new(1));
+        let (r, w) = create();
+
+        let mut pin = unsafe { w.pin_to(&src) };
+        assert_eq!(pin.get(), Some(&1));
+        let first = pin.as_ptr();
+        assert_eq!(r.get(), State::Protect(first as *const u8));
+
+        // the source moves on, and the pin keeps the old object protected until refreshed
+        let second = Box::into_raw(Box::new(2));
+        let old = unsafe { src.get_inner() }.swap(second, Ordering::AcqRel);
+        assert_eq!(old, first);
+        assert_eq!(r.get(), State::Protect(first as *const u8));
+
+        assert_eq!(pin.refresh(), Some(&2));
+        assert_eq!(pin.as_ptr(), second);
+        assert_eq!(r.get(), State::Protect(second as *const u8));
+        // no longer protected, so the old object can be reclaimed
+        drop(unsafe { Box::from_raw(old) });
+
+        let old = unsafe { src.get_inner() }.swap(ptr::null_mut(), Ordering::AcqRel);
+        assert_eq!(pin.refresh(), None);
+        assert_eq!(r.get(), State::Free);
+        drop(unsafe { Box::from_raw(old) });
+
+        drop(pin);
+        assert_eq!(r.get(), State::Free);
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn atomic_value_cas() {
+        let value = AtomicValue::new(5u32);
+        assert_eq!(value.load(Ordering::Acquire), 5);
+
+        assert_eq!(
+            value.compare_exchange(5, 6, Ordering::AcqRel, Ordering::Acquire),
+            Ok(5)
+        );
+        assert_eq!(
+            value.compare_exchange(5, 7, Ordering::AcqRel, Ordering::Acquire),
+            Err(6)
+        );
+        assert_eq!(value.swap(u32::MAX, Ordering::AcqRel), 6);
+        value.store(0, Ordering::Release);
+        assert_eq!(value.into_inner(), 0);
+
+        // narrower and signed types round-trip through the wider word
+        let flag = AtomicValue::new(false);
+        assert_eq!(
+            flag.compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire),
+            Ok(false)
+        );
+        assert!(flag.load(Ordering::Acquire));
+        assert_eq!(AtomicValue::new(-3i16).load(Ordering::Acquire), -3);
+        assert_eq!(AtomicValue::new(1.5f64).load(Ordering::Acquire), 1.5);
+    }
+
+    #[test]
+    #[cfg(feature = "testing")]
+    fn atomic_value_cas_contended() {
+        let counter = AtomicValue::new(0u32);
+        StressHarness::new(4, 1_000).run(|| {
+            let mut current = counter.load(Ordering::Acquire);
+            while let Err(actual) =
+                counter.compare_exchange(current, current + 1, Ordering::AcqRel, Ordering::Acquire)
+            {
+                current = actual;
+            }
+        });
+        assert_eq!(counter.into_inner(), 4_000);
+    }
+
+    #[test]
+    fn try_into_box() {
+        let a = Atomic::from_box(Box::new(3));
+        assert_eq!(a.try_into_box(), Some(Box::new(3)));
+
+        let a: Atomic<i32> = Atomic::new(None);
+        assert_eq!(a.try_into_box(), None);
+
+        let converted: Option<Box<i32>> = Atomic::from_box(Box::new(4)).into();
+        assert_eq!(converted.as_deref(), Some(&4));
+    }
+
+    #[test]
+    fn swap_with_backoff() {
+        let a = Atomic::from_box(Box::new(1));
+
+        // every swap takes one box out for the one it puts in, so none are lost
+        thread::scope(|s| {
+            for id in 2..6 {
+                let a = &a;
+                s.spawn(move || {
+                    for _ in 0..100 {
+                        assert!(a
+                            .swap_with_backoff(Some(Box::new(id)), Ordering::AcqRel)
+                            .is_some());
+                    }
+                });
+            }
+        });
+
+        let last = a.swap_with_backoff(None, Ordering::AcqRel).unwrap();
+        assert!((2..6).contains(&*last));
+        assert!(a.swap_with_backoff(None, Ordering::AcqRel).is_none());
+    }
+
+    #[test]
+    fn store_ptr() {
+        let a: Atomic<u32> = Atomic::new(None);
+        let leaked = Box::into_raw(Box::new(11u32));
+
+        unsafe { a.store_ptr(leaked, Ordering::Release) };
+        assert_eq!(a.load(Ordering::Acquire), leaked);
+        assert_eq!(unsafe { *a.load(Ordering::Acquire) }, 11);
+
+        // the atomic never frees what it holds, so the caller reclaims it exactly once
+        unsafe { a.store_ptr(ptr::null_mut(), Ordering::Release) };
+        assert!(a.try_into_box().is_none());
+        drop(unsafe { Box::from_raw(leaked) });
+    }
+
+    #[test]
+    fn inspect_while_swapping() {
+        const SWAPS: usize = if cfg!(miri) { 20 } else { 1_000 };
+
+        let a = Atomic::from_box(Box::new(0usize));
+        let done = atomic::AtomicBool::new(false);
+        let (r, w) = create();
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                for i in 1..=SWAPS {
+                    let old =
+                        unsafe { a.get_inner() }.swap(Box::into_raw(Box::new(i)), Ordering::SeqCst);
+                    // order the unlink before the hazard scan
+                    atomic::fence(Ordering::SeqCst);
+                    while r.get() == State::Protect(old as *const u8) {
+                        thread::yield_now();
+                    }
+                    drop(unsafe { Box::from_raw(old) });
+                }
+                done.store(true, Ordering::Release);
+            });
+
+            let mut last = 0;
+            while !done.load(Ordering::Acquire) {
+                let value = unsafe { a.inspect(&w, |value| *value.unwrap()) };
+                assert!(value >= last, "values go backwards");
+                last = value;
+            }
+        });
+
+        assert_eq!(r.get(), State::Free);
+        assert_eq!(
+            unsafe { a.inspect(&w, |value| value.copied()) },
+            Some(SWAPS)
+        );
+        drop(a.try_into_box());
+        let empty: Atomic<usize> = Atomic::new(None);
+        assert!(unsafe { empty.inspect(&w, |value| value.is_none()) });
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+}
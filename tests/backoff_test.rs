@@ -0,0 +1,26 @@
+#[cfg(test)]
+mod backoff_tests {
+    use STM::backoff::Backoff;
+
+    #[test]
+    fn snooze_completes_and_resets() {
+        let backoff = Backoff::new();
+        assert!(!backoff.is_completed());
+
+        let mut snoozes = 0;
+        while !backoff.is_completed() {
+            backoff.snooze();
+            snoozes += 1;
+        }
+        assert!(snoozes > 1);
+
+        backoff.reset();
+        assert!(!backoff.is_completed());
+
+        // spinning alone never escalates to completion
+        for _ in 0..100 {
+            backoff.spin();
+        }
+        assert!(!backoff.is_completed());
+    }
+}
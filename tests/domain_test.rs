@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod domain_tests {
+    use STM::domain::StaticHazardDomain;
+
+    static DOMAIN: StaticHazardDomain<4> = StaticHazardDomain::new();
+
+    #[test]
+    fn acquire_and_release_all_slots() {
+        let values = [1u8, 2, 3, 4];
+        assert_eq!(DOMAIN.capacity(), 4);
+
+        let shields: Vec<_> = (0..4).map(|_| DOMAIN.acquire().unwrap()).collect();
+        assert!(DOMAIN.acquire().is_none());
+
+        for (shield, value) in shields.iter().zip(&values) {
+            shield.protect(value);
+        }
+        assert!(values.iter().all(|v| DOMAIN.is_protected(v)));
+
+        shields[0].free();
+        assert!(!DOMAIN.is_protected(&values[0]));
+
+        drop(shields);
+        assert!(values.iter().all(|v| !DOMAIN.is_protected(v)));
+
+        let again: Vec<_> = (0..4).map(|_| DOMAIN.acquire().unwrap()).collect();
+        assert_eq!(again.len(), 4);
+    }
+
+    #[test]
+    fn total_acquisitions() {
+        let domain: StaticHazardDomain<2> = StaticHazardDomain::new();
+        assert_eq!(domain.total_acquisitions(), 0);
+
+        for _ in 0..10 {
+            let shield = domain.acquire().unwrap();
+            drop(shield);
+        }
+        assert_eq!(domain.total_acquisitions(), 10);
+
+        // a failed acquisition is not counted
+        let _a = domain.acquire().unwrap();
+        let _b = domain.acquire().unwrap();
+        assert!(domain.acquire().is_none());
+        assert_eq!(domain.total_acquisitions(), 12);
+    }
+
+    #[test]
+    fn assert_not_protected_passes_for_unprotected() {
+        let domain: StaticHazardDomain<2> = StaticHazardDomain::new();
+        let x = 1u8;
+
+        let shield = domain.acquire().unwrap();
+        assert!(domain.assert_not_protected(&x));
+
+        shield.protect(&x);
+        shield.free();
+        assert!(domain.assert_not_protected(&x));
+    }
+
+    #[test]
+    #[cfg_attr(
+        debug_assertions,
+        should_panic(expected = "while it is hazard-protected")
+    )]
+    fn assert_not_protected_catches_protected() {
+        let domain: StaticHazardDomain<2> = StaticHazardDomain::new();
+        let x = 1u8;
+
+        let shield = domain.acquire().unwrap();
+        shield.protect(&x);
+        assert!(!domain.assert_not_protected(&x));
+    }
+}
@@ -1,8 +1,22 @@
 #[cfg(test)]
 mod hazard_tests {
-    use std::{ptr, thread};
+    use std::{
+        panic::{self, AssertUnwindSafe},
+        ptr::{self, NonNull},
+        sync::{
+            atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering},
+            mpsc, Arc,
+        },
+        thread,
+        time::Duration,
+    };
 
-    use STM::hazard::{create, State};
+    #[cfg(feature = "testing")]
+    use STM::hazard::sentinels;
+    use STM::hazard::{
+        blocked_sentinel, builder, create, create_seqcst, dead_sentinel, free_sentinel,
+        invalidated_sentinel, JoinableHazard, Parker, SpinParker, State, ThreadParker,
+    };
 
     #[test]
     fn test_set_and_get() {
@@ -25,8 +39,8 @@ mod hazard_tests {
         w.protect(ptr::null());
         assert_eq!(r.get(), State::Protect(ptr::null()));
 
-        w.protect(0x1 as *const u8);
-        assert_eq!(r.get(), State::Protect(0x1 as *const u8));
+        w.protect(ptr::dangling::<u8>());
+        assert_eq!(r.get(), State::Protect(ptr::dangling::<u8>()));
 
         w.kill();
         unsafe {
@@ -58,10 +72,14 @@ mod hazard_tests {
 
             thread::spawn(move || {
                 w.kill();
-            }).join().unwrap();
+            })
+            .join()
+            .unwrap();
 
             assert_eq!(r.get(), State::Dead);
-            unsafe { r.destroy(); }
+            unsafe {
+                r.destroy();
+            }
         }
     }
 
@@ -75,4 +93,732 @@ mod hazard_tests {
             }
         }
     }
+
+    #[test]
+    fn free_if_protecting() {
+        let (r, w) = create();
+        let x = 3;
+
+        assert!(!w.free_if_protecting());
+        assert!(w.is_blocked());
+
+        w.protect(&x);
+        assert!(w.free_if_protecting());
+        assert_eq!(r.get(), State::Free);
+
+        assert!(!w.free_if_protecting());
+        assert_eq!(r.get(), State::Free);
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn get_timeout() {
+        let (r, w) = create();
+        let (tx, rx) = mpsc::channel::<()>();
+
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            w.free();
+            rx.recv().unwrap();
+            w.kill();
+        });
+
+        assert_eq!(r.get_timeout(Duration::from_millis(10)), Err(()));
+        assert_eq!(r.get_timeout(Duration::from_millis(500)), Ok(State::Free));
+
+        tx.send(()).unwrap();
+        handle.join().unwrap();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn seqcst_set_and_get() {
+        let (r, w) = create_seqcst();
+        assert!(w.is_blocked());
+
+        w.free();
+        assert_eq!(r.get(), State::Free);
+
+        let x = 42;
+        w.protect(&x);
+        assert_eq!(r.get(), State::Protect(&x));
+        assert!(w.free_if_protecting());
+        assert_eq!(r.get(), State::Free);
+
+        w.kill();
+        assert_eq!(r.get(), State::Dead);
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn seqcst_total_order() {
+        // store buffering: each thread publishes on its own hazard, then reads the other one.
+        // under a single total order, at least one of them must observe the other's protection.
+        for _ in 0..1_000 {
+            let (ra, wa) = create_seqcst();
+            let (rb, wb) = create_seqcst();
+            let (x, y) = (1u8, 2u8);
+            wa.free();
+            wb.free();
+
+            let (a_missed, b_missed) = thread::scope(|s| {
+                let a = s.spawn(|| {
+                    wa.protect(&x);
+                    rb.get() == State::Free
+                });
+                let b = s.spawn(|| {
+                    wb.protect(&y);
+                    ra.get() == State::Free
+                });
+                (a.join().unwrap(), b.join().unwrap())
+            });
+
+            assert!(!(a_missed && b_missed));
+
+            wa.kill();
+            wb.kill();
+            unsafe {
+                ra.destroy();
+                rb.destroy();
+            }
+        }
+    }
+
+    #[test]
+    fn with_protected() {
+        let (r, w) = create();
+        let boxed = Box::new(42i32);
+
+        w.free();
+        assert_eq!(
+            unsafe { r.with_protected(|v: Option<&i32>| v.copied()) },
+            None
+        );
+
+        w.protect(&*boxed as *const i32 as *const u8);
+        assert_eq!(
+            unsafe { r.with_protected(|v: Option<&i32>| v.copied()) },
+            Some(42)
+        );
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn get_cooperative_helps_announced_transition() {
+        let (r, w) = create();
+        let x = 1u8;
+
+        w.protect(&x);
+        w.block_announcing(State::Free);
+        assert!(w.is_blocked());
+
+        // the reader completes the free transition instead of spinning
+        assert_eq!(r.get_cooperative(), State::Free);
+        assert!(!w.is_blocked());
+
+        w.complete_announced();
+        assert_eq!(r.get(), State::Free);
+
+        // when no reader helps, the writer completes the transition itself
+        w.block_announcing(State::Protect(&x));
+        w.complete_announced();
+        assert_eq!(r.get(), State::Protect(&x));
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    // the pair is leaked by the panic, which Miri's leak check would report
+    #[cfg_attr(miri, ignore)]
+    #[should_panic(expected = "call complete_announced first")]
+    fn transition_with_pending_announcement() {
+        let (_r, w) = create();
+        let x = 1u8;
+
+        w.block_announcing(State::Free);
+        // a reader could still install the announced free over this protection
+        w.protect(&x);
+    }
+
+    #[test]
+    fn get_cooperative_concurrent() {
+        let (r, w) = create();
+        let nodes = [1u8, 2, 3, 4];
+        w.free();
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                for i in 0..10_000 {
+                    w.block_announcing(State::Protect(&nodes[i % nodes.len()]));
+                    w.complete_announced();
+                }
+            });
+
+            for _ in 0..10_000 {
+                match r.get_cooperative() {
+                    State::Free => {}
+                    State::Protect(p) => assert!(nodes.iter().any(|n| ptr::eq(n, p))),
+                    State::Dead => panic!("hazard died unexpectedly"),
+                    State::Blocked => unreachable!("get_cooperative never returns blocked"),
+                    State::Invalidated => panic!("protection invalidated unexpectedly"),
+                }
+            }
+        });
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn rebind() {
+        let (mut r, wa) = create();
+        let (rb, wb) = create();
+        let x = 1u8;
+
+        wa.free();
+        wb.protect(&x);
+        assert_eq!(r.get(), State::Free);
+
+        let old = r.rebind(rb);
+        assert_eq!(r.get(), State::Protect(&x));
+        assert_eq!(old.get(), State::Free);
+
+        wa.kill();
+        wb.kill();
+        unsafe {
+            old.destroy();
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn protect_nonnull() {
+        let (r, w) = create();
+        let mut x = 5u8;
+        let mut y = 7u64;
+
+        let px = NonNull::from(&mut x);
+        w.protect_nonnull(px);
+        assert_eq!(r.get(), State::Protect(px.as_ptr()));
+
+        let py = NonNull::from(&mut y);
+        w.protect_nonnull_typed(py);
+        assert_eq!(r.get(), State::Protect(py.as_ptr() as *const u8));
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn diagnose() {
+        let (r, w) = create();
+
+        // the writer never leaves the initial blocked state
+        assert_eq!(r.get_timeout(Duration::from_millis(10)), Err(()));
+        let diagnosis = r.diagnose();
+        assert!(diagnosis.blocked);
+        assert!(diagnosis.spins > 0);
+        assert!(!diagnosis.ever_unblocked);
+        // diagnosing does not disturb the counters it reports
+        assert_eq!(r.diagnose(), diagnosis);
+
+        w.free();
+        assert_eq!(r.get(), State::Free);
+        let diagnosis = r.diagnose();
+        assert!(!diagnosis.blocked);
+        assert!(diagnosis.ever_unblocked);
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn joinable_hazard() {
+        static X: u8 = 4;
+        let (tx, rx) = mpsc::channel::<()>();
+
+        let hazard = JoinableHazard::spawn(move |w| {
+            w.protect(&X);
+            rx.recv().unwrap();
+            w.free();
+            42
+        });
+
+        assert_eq!(hazard.reader().get(), State::Protect(&X));
+        tx.send(()).unwrap();
+        assert_eq!(hazard.join().unwrap(), 42);
+    }
+
+    #[test]
+    fn joinable_hazard_worker_panics() {
+        let hazard = JoinableHazard::spawn(|w| {
+            w.free();
+            panic!("worker failed");
+        });
+
+        let err = hazard.join().unwrap_err();
+        assert_eq!(err.downcast_ref::<&str>(), Some(&"worker failed"));
+    }
+
+    #[test]
+    fn protect_fat() {
+        let (r, w) = create();
+        let data: Box<[u8]> = vec![1, 2, 3].into_boxed_slice();
+        let fat: *const [u8] = &*data;
+
+        w.protect_fat(fat);
+        assert_eq!(r.get(), State::Protect(data.as_ptr()));
+
+        let object: Box<dyn std::fmt::Debug> = Box::new(7u32);
+        w.protect_fat(&*object as *const dyn std::fmt::Debug);
+        assert_eq!(r.get(), State::Protect(&*object as *const _ as *const u8));
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn destroy_if_dead() {
+        let (r, w) = create();
+
+        // still blocked, and then live: the reader is handed back
+        let r = r.destroy_if_dead().unwrap_err();
+        w.free();
+        let r = r.destroy_if_dead().unwrap_err();
+
+        w.kill();
+        assert!(r.destroy_if_dead().is_ok());
+    }
+
+    #[test]
+    fn protect_null_constant() {
+        assert_eq!(State::Protect(ptr::null()), State::PROTECT_NULL);
+        assert_ne!(State::Free, State::PROTECT_NULL);
+        assert_eq!(format!("{:?}", State::PROTECT_NULL), "Protect(null)");
+
+        let (r, w) = create();
+        w.protect(ptr::null());
+        assert_eq!(r.get(), State::PROTECT_NULL);
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn swap_state_returns_prior() {
+        let (r, w) = create();
+        assert_eq!(w.swap_state(State::Free), State::Blocked);
+        assert_eq!(r.get(), State::Free);
+
+        let x = 7u8;
+        assert_eq!(w.swap_state(State::Protect(&x)), State::Free);
+        assert_eq!(w.swap_state(State::Blocked), State::Protect(&x));
+        assert!(w.is_blocked());
+        assert_eq!(w.swap_state(State::Free), State::Blocked);
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn observe_raw_matches_sentinel() {
+        let (r, w) = create();
+        w.free();
+        assert_eq!(r.observe_raw(), free_sentinel());
+
+        let x = 7u8;
+        w.protect(&x);
+        assert_eq!(r.observe_raw(), &x as *const u8);
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn sentinels_match_observed_words() {
+        let (r, w) = create();
+        assert_eq!(r.observe_raw(), blocked_sentinel());
+
+        w.free();
+        assert_eq!(r.observe_raw(), free_sentinel());
+
+        w.kill();
+        assert_eq!(r.observe_raw(), dead_sentinel());
+        unsafe {
+            r.destroy();
+        }
+
+        let sentinels = [blocked_sentinel(), free_sentinel(), dead_sentinel()];
+        assert_ne!(sentinels[0], sentinels[1]);
+        assert_ne!(sentinels[1], sentinels[2]);
+        assert_ne!(sentinels[0], sentinels[2]);
+    }
+
+    #[test]
+    fn get_with_parker() {
+        struct Recording {
+            parks: AtomicUsize,
+        }
+
+        impl Parker for Recording {
+            fn should_park(&self, spins: usize) -> bool {
+                spins >= 16
+            }
+
+            fn park(&self) {
+                self.parks.fetch_add(1, Ordering::SeqCst);
+                thread::yield_now();
+            }
+        }
+
+        let (r, w) = create();
+        let parker = Recording {
+            parks: AtomicUsize::new(0),
+        };
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                // stay blocked until the reader has given up spinning
+                while parker.parks.load(Ordering::SeqCst) == 0 {
+                    thread::yield_now();
+                }
+                w.free();
+            });
+
+            assert_eq!(r.get_with_parker(&parker), State::Free);
+        });
+        assert!(parker.parks.load(Ordering::SeqCst) > 0);
+
+        // the provided parkers resolve an unblocked hazard straight away
+        assert_eq!(r.get_with_parker(&SpinParker), State::Free);
+        assert_eq!(r.get_with_parker(&ThreadParker::default()), State::Free);
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn protect_from_raw_atomic_ptr() {
+        static NODES: [u64; 4] = [0, 1, 2, 3];
+
+        let source = AtomicPtr::new(&NODES[0] as *const u64 as *mut u64);
+        let done = AtomicBool::new(false);
+        let (r, w) = create();
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                let mut i = 0;
+                while !done.load(Ordering::Relaxed) {
+                    i += 1;
+                    let next = &NODES[i % NODES.len()] as *const u64 as *mut u64;
+                    source.store(next, Ordering::Release);
+                }
+            });
+
+            for _ in 0..1_000 {
+                let ptr = w.protect_from(|| source.load(Ordering::Acquire));
+                assert!(NODES.iter().any(|n| ptr::eq(n, ptr)));
+                assert_eq!(r.get(), State::Protect(ptr as *const u8));
+            }
+            done.store(true, Ordering::Relaxed);
+        });
+
+        let null = AtomicPtr::<u64>::new(ptr::null_mut());
+        assert!(w.protect_from(|| null.load(Ordering::Acquire)).is_null());
+        assert_eq!(r.get(), State::Free);
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn get_cancellable() {
+        let (r, w) = create();
+        let cancel = AtomicBool::new(false);
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                thread::sleep(Duration::from_millis(50));
+                cancel.store(true, Ordering::Release);
+            });
+
+            // the writer never unblocks, so only the flag can end the wait
+            assert_eq!(r.get_cancellable(&cancel), None);
+        });
+
+        w.free();
+        assert_eq!(r.get_cancellable(&cancel), Some(State::Free));
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    // the pair is leaked by the panic, which Miri's leak check would report
+    #[cfg_attr(miri, ignore)]
+    #[cfg_attr(
+        debug_assertions,
+        should_panic(expected = "correct order is kill() then destroy()")
+    )]
+    #[cfg_attr(
+        not(debug_assertions),
+        should_panic(expected = "hazard pointer is not dead")
+    )]
+    fn destroy_before_kill() {
+        let (r, w) = create();
+        w.free();
+
+        // wrong order: the writer is still live
+        unsafe {
+            r.destroy();
+        }
+        w.kill();
+    }
+
+    #[test]
+    fn invalidate_protection() {
+        let (r, w) = create();
+        let old = 1u8;
+        let new = 2u8;
+
+        // invalidating without a protection does nothing
+        w.free();
+        assert!(!w.invalidate_protection());
+        assert_eq!(r.get(), State::Free);
+
+        w.protect(&old);
+        assert!(w.invalidate_protection());
+        assert_eq!(r.observe_raw(), invalidated_sentinel());
+        assert!(!w.invalidate_protection());
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                thread::sleep(Duration::from_millis(10));
+                w.protect(&new);
+            });
+
+            // the reader discards the stale protection and retries until it is re-acquired
+            loop {
+                match r.get() {
+                    State::Invalidated => thread::yield_now(),
+                    State::Protect(ptr) => {
+                        assert!(ptr::eq(ptr, &new));
+                        break;
+                    }
+                    other => panic!("unexpected state {:?}", other),
+                }
+            }
+        });
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn builder_policy() {
+        struct Recording {
+            parks: Arc<AtomicUsize>,
+        }
+
+        impl Parker for Recording {
+            fn should_park(&self, _spins: usize) -> bool {
+                false
+            }
+
+            fn park(&self) {
+                self.parks.fetch_add(1, Ordering::SeqCst);
+                thread::yield_now();
+            }
+        }
+
+        let parks = Arc::new(AtomicUsize::new(0));
+        // the spin limit overrides the parker's own choice never to park
+        let (r, w) = builder()
+            .spin_limit(2)
+            .parker(Recording {
+                parks: parks.clone(),
+            })
+            .seqcst(true)
+            .build();
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                while parks.load(Ordering::SeqCst) == 0 {
+                    thread::yield_now();
+                }
+                w.free();
+            });
+
+            assert_eq!(r.get(), State::Free);
+        });
+        assert!(parks.load(Ordering::SeqCst) > 0);
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn protect_catch() {
+        let (r, w) = create();
+        let x = 3u8;
+
+        let result = w.protect_catch(&x, || {
+            assert_eq!(r.get(), State::Protect(&x));
+            x + 1
+        });
+        assert_eq!(result.unwrap(), 4);
+        assert_eq!(r.get(), State::Free);
+
+        let result = w.protect_catch(&x, || -> u8 { panic!("callback failed") });
+        let payload = result.unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"callback failed"));
+        assert_eq!(r.get(), State::Free);
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "testing")]
+    fn try_protect_rejects_dead() {
+        let (r, w) = create();
+        let x = 5u8;
+
+        assert_eq!(w.try_protect(&x), Ok(()));
+        assert_eq!(r.get(), State::Protect(&x));
+
+        unsafe { w.kill_in_place() };
+        assert_eq!(w.try_protect(&x), Err(()));
+        assert_eq!(r.get(), State::Dead);
+
+        std::mem::forget(w);
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn protect_rejects_sentinels() {
+        let (r, w) = create();
+
+        // a protection that reads as dead would let destroy_if_dead free the slot under `w`
+        let protect = panic::catch_unwind(AssertUnwindSafe(|| w.protect(dead_sentinel())));
+        assert!(protect.is_err());
+        let try_protect = panic::catch_unwind(AssertUnwindSafe(|| w.try_protect(dead_sentinel())));
+        assert!(try_protect.is_err());
+        // a reader would install the announced word on the writer's behalf
+        let announce = panic::catch_unwind(AssertUnwindSafe(|| {
+            w.block_announcing(State::Protect(dead_sentinel()))
+        }));
+        assert!(announce.is_err());
+        let swap = panic::catch_unwind(AssertUnwindSafe(|| {
+            w.swap_state(State::Protect(dead_sentinel()))
+        }));
+        assert!(swap.is_err());
+
+        assert!(w.is_blocked());
+        let r = r.destroy_if_dead().unwrap_err();
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    fn get_waits_out_long_block() {
+        let (r, w) = create();
+
+        // long enough that `reader-blocking` gets past spinning and yields
+        thread::scope(|s| {
+            s.spawn(|| {
+                thread::sleep(Duration::from_millis(20));
+                w.free();
+            });
+
+            assert_eq!(r.get(), State::Free);
+        });
+
+        w.kill();
+        unsafe {
+            r.destroy();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "testing")]
+    fn sentinels_decode_raw_words() {
+        let sentinels = sentinels();
+        let decode = |raw: *const u8| {
+            if raw == sentinels.blocked {
+                "blocked"
+            } else if raw == sentinels.free {
+                "free"
+            } else if raw == sentinels.dead {
+                "dead"
+            } else if raw == sentinels.invalidated {
+                "invalidated"
+            } else {
+                "protect"
+            }
+        };
+
+        let (r, w) = create();
+        let x = 1u8;
+        assert_eq!(decode(r.observe_raw()), "blocked");
+        w.free();
+        assert_eq!(decode(r.observe_raw()), "free");
+        w.protect(&x);
+        assert_eq!(decode(r.observe_raw()), "protect");
+        w.invalidate_protection();
+        assert_eq!(decode(r.observe_raw()), "invalidated");
+        w.kill();
+        assert_eq!(decode(r.observe_raw()), "dead");
+
+        unsafe {
+            r.destroy();
+        }
+    }
 }
@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod local_tests {
+    use std::{ptr, sync::atomic::Ordering};
+
+    use STM::atomic::{Atomic, PointerCell};
+    use STM::local::LocalAtomic;
+
+    struct Node {
+        value: usize,
+        next: *mut Node,
+    }
+
+    /// push `0..n` onto a chain and return the values in pop order, freeing the nodes
+    fn push_and_drain<C: PointerCell<Node>>(n: usize) -> Vec<usize> {
+        let head = C::new(None);
+
+        for value in 0..n {
+            head.cas_loop(|next| Some(Box::new(Node { value, next })));
+        }
+
+        let mut values = Vec::new();
+        let (mut cur, _) = head.exchange_reporting(None, Ordering::AcqRel);
+        while let Some(node) = cur {
+            values.push(node.value);
+            cur = (!node.next.is_null()).then(|| unsafe { Box::from_raw(node.next) });
+        }
+
+        values
+    }
+
+    #[test]
+    fn generic_over_both_cells() {
+        let expected: Vec<_> = (0..100).rev().collect();
+
+        assert_eq!(push_and_drain::<Atomic<Node>>(100), expected);
+        assert_eq!(push_and_drain::<LocalAtomic<Node>>(100), expected);
+    }
+
+    #[test]
+    fn compare_exchange() {
+        let mut cell = LocalAtomic::from_box(Box::new(1usize));
+        let current = cell.load(Ordering::Relaxed);
+
+        let (rejected, actual) = cell
+            .compare_exchange(
+                ptr::null_mut(),
+                Some(Box::new(2)),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            )
+            .unwrap_err();
+        assert_eq!(rejected.as_deref(), Some(&2));
+        assert_eq!(actual, current);
+
+        let old = cell
+            .compare_exchange(
+                current,
+                Some(Box::new(3)),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            )
+            .unwrap();
+        assert_eq!(*unsafe { Box::from_raw(old) }, 1);
+        assert_eq!(cell.get_mut().copied(), Some(3));
+
+        let (last, _) = cell.exchange_reporting(None, Ordering::Relaxed);
+        assert_eq!(last.as_deref(), Some(&3));
+    }
+}
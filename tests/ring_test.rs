@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod ring_tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+    };
+
+    use STM::ring::SpscRing;
+
+    #[test]
+    fn full_and_empty() {
+        let (mut tx, mut rx) = SpscRing::new(3);
+        assert_eq!(rx.pop(), None);
+
+        for i in 0..3 {
+            tx.push(i).unwrap();
+        }
+        assert_eq!(tx.push(3), Err(3));
+
+        // wrap around the end of the buffer a few times
+        for i in 0..10 {
+            assert_eq!(rx.pop(), Some(i));
+            tx.push(i + 3).unwrap();
+        }
+        assert_eq!(rx.pop(), Some(10));
+        assert_eq!(rx.pop(), Some(11));
+        assert_eq!(rx.pop(), Some(12));
+        assert_eq!(rx.pop(), None);
+    }
+
+    #[test]
+    fn transfer_across_threads() {
+        const ITEMS: usize = if cfg!(miri) { 1_000 } else { 1_000_000 };
+
+        let (mut tx, mut rx) = SpscRing::new(64);
+
+        let producer = thread::spawn(move || {
+            for i in 0..ITEMS {
+                let mut value = i;
+                while let Err(rejected) = tx.push(value) {
+                    value = rejected;
+                    thread::yield_now();
+                }
+            }
+        });
+
+        // values arrive in order, so none were lost or duplicated
+        let mut expected = 0;
+        while expected < ITEMS {
+            match rx.pop() {
+                Some(value) => {
+                    assert_eq!(value, expected);
+                    expected += 1;
+                }
+                None => thread::yield_now(),
+            }
+        }
+        assert_eq!(rx.pop(), None);
+
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn drop_frees_unpopped_values() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Counted;
+
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let (mut tx, mut rx) = SpscRing::new(4);
+        for _ in 0..4 {
+            assert!(tx.push(Counted).is_ok());
+        }
+        drop(rx.pop());
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+
+        drop(tx);
+        drop(rx);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn drop_after_wrap() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Counted;
+
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        // go around the index range more than once, so the live values straddle its end
+        let (mut tx, mut rx) = SpscRing::new(3);
+        for _ in 0..7 {
+            assert!(tx.push(Counted).is_ok());
+            drop(rx.pop());
+        }
+        for _ in 0..3 {
+            assert!(tx.push(Counted).is_ok());
+        }
+        assert!(tx.push(Counted).is_err());
+        assert_eq!(DROPS.load(Ordering::Relaxed), 8);
+
+        drop(tx);
+        drop(rx);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 11);
+    }
+}
@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod teardown_tests {
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+        panic,
+    };
+
+    use STM::hazard::{create, reclaim_pair, with_pair, State};
+
+    /// counts the bytes currently allocated by each thread
+    struct Tracking;
+
+    thread_local! {
+        static LIVE: Cell<isize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for Tracking {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            LIVE.with(|live| live.set(live.get() + layout.size() as isize));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            LIVE.with(|live| live.set(live.get() - layout.size() as isize));
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: Tracking = Tracking;
+
+    fn live() -> isize {
+        LIVE.with(Cell::get)
+    }
+
+    #[test]
+    fn reclaims_after_closure() {
+        let before = live();
+
+        let value = with_pair(|r, w| {
+            let x = 5;
+            w.protect(&x);
+            assert_eq!(r.get(), State::Protect(&x));
+            w.free();
+            assert_eq!(r.get(), State::Free);
+            x
+        });
+
+        assert_eq!(value, 5);
+        assert_eq!(live(), before);
+    }
+
+    #[test]
+    fn reclaims_on_panic() {
+        panic::set_hook(Box::new(|_| {}));
+        // warm up the panic machinery so its one-time allocations are not counted
+        let _ = panic::catch_unwind(|| panic!("warm up"));
+
+        let before = live();
+
+        let result = panic::catch_unwind(|| {
+            with_pair(|_, w| {
+                w.free();
+                panic!("boom");
+            })
+        });
+
+        assert!(result.is_err());
+        drop(result);
+        let _ = panic::take_hook();
+        assert_eq!(live(), before);
+    }
+
+    #[test]
+    fn reclaim_pair_frees_allocation() {
+        let before = live();
+
+        let (r, w) = create();
+        let x = 9;
+        w.protect(&x);
+        assert!(live() > before);
+
+        unsafe {
+            reclaim_pair(r, w);
+        }
+        assert_eq!(live(), before);
+    }
+
+    /// Run with `cargo +nightly miri test --test teardown_test`: Miri fails the run if the slot
+    /// leaks, or if teardown leaves behind a reader holding a dangling reference.
+    #[test]
+    fn reclaim_is_clean_under_miri() {
+        for _ in 0..3 {
+            let (r, w) = create();
+            let x = 1;
+            w.protect(&x);
+            unsafe {
+                reclaim_pair(r, w);
+            }
+
+            let (r, w) = create();
+            w.kill();
+            unsafe {
+                r.destroy();
+            }
+
+            let (r, w) = create();
+            w.kill();
+            assert!(r.destroy_if_dead().is_ok());
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    // the mismatched halves are leaked, which Miri's leak check would report
+    #[cfg_attr(miri, ignore)]
+    #[should_panic(expected = "different hazard pairs")]
+    fn reclaim_pair_rejects_mismatched_halves() {
+        let (r1, _w1) = create();
+        let (_r2, w2) = create();
+
+        unsafe {
+            reclaim_pair(r1, w2);
+        }
+    }
+}
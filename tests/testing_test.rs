@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod testing_tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use STM::hazard::{create, State};
+    use STM::testing::StressHarness;
+
+    #[test]
+    fn runs_every_iteration() {
+        let counter = AtomicUsize::new(0);
+
+        StressHarness::new(8, 1_000).run(|| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+
+        assert_eq!(counter.load(Ordering::Relaxed), 8_000);
+    }
+
+    #[test]
+    fn single_threaded_fallback() {
+        let counter = AtomicUsize::new(0);
+
+        StressHarness::new(4, 10)
+            .single_threaded(true)
+            .run_indexed(|id| {
+                assert!(id < 4);
+                counter.fetch_add(1, Ordering::Relaxed);
+            });
+
+        assert_eq!(counter.load(Ordering::Relaxed), 40);
+    }
+
+    #[test]
+    #[should_panic(expected = "stress threads panicked")]
+    fn reports_panics() {
+        StressHarness::new(4, 10).run_indexed(|id| {
+            if id == 2 {
+                panic!("boom");
+            }
+        });
+    }
+
+    #[test]
+    fn hazard_pairs() {
+        StressHarness::new(8, 1_000).run(|| {
+            let (r, w) = create();
+            let x = 7;
+
+            w.protect(&x);
+            assert_eq!(r.get(), State::Protect(&x));
+            w.kill();
+            unsafe {
+                r.destroy();
+            }
+        });
+    }
+}
@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod trace_tests {
+    use std::sync::Mutex;
+
+    use log::{Level, LevelFilter, Log, Metadata, Record};
+    use STM::hazard::{create, State};
+
+    /// collects every hazard trace line emitted by the crate
+    struct Capture {
+        lines: Mutex<Vec<String>>,
+    }
+
+    impl Log for Capture {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.level() == Level::Trace
+        }
+
+        fn log(&self, record: &Record) {
+            if record.target().starts_with("STM::hazard") {
+                self.lines.lock().unwrap().push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: Capture = Capture {
+        lines: Mutex::new(Vec::new()),
+    };
+
+    #[test]
+    fn protect_get_kill() {
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(LevelFilter::Trace);
+
+        let (r, w) = create();
+        let x = 1u8;
+
+        w.protect(&x);
+        assert_eq!(r.get(), State::Protect(&x));
+        w.kill();
+
+        let lines = LOGGER.lines.lock().unwrap().clone();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].contains(&format!("protect Blocked -> Protect({:p})", &x)));
+        assert!(lines[1].contains(&format!("get -> Protect({:p})", &x)));
+        assert!(lines[2].contains(&format!("kill Protect({:p}) -> Dead", &x)));
+
+        unsafe {
+            r.destroy();
+        }
+    }
+}